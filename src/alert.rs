@@ -0,0 +1,353 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::sync::mpsc;
+#[cfg(not(target_arch = "wasm32"))]
+use tokio_stream::wrappers::ReceiverStream;
+#[cfg(not(target_arch = "wasm32"))]
+use tokio_stream::Stream;
+
+use crate::oklink::{LargeTransaction, Oklink, TokenTransfer};
+use crate::transport::TransportError;
+use crate::valuation::price_token;
+
+// How many delivered alerts are remembered for deduplication.
+const SEEN_CAPACITY: usize = 10_000;
+
+#[derive(Debug)]
+pub enum AlertError {
+    Http(TransportError),
+    // A `Threshold::Usd` rule's token has no price, keyed as in `set_price`.
+    MissingPrice(String),
+}
+
+impl From<TransportError> for AlertError {
+    fn from(e: TransportError) -> Self {
+        AlertError::Http(e)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Threshold {
+    Units(f64),
+    Usd(f64),
+}
+
+#[derive(Debug, Clone)]
+pub struct AlertRule {
+    pub name: String,
+    // None watches native transfers through `large_transaction_list`.
+    pub token_contract_address: Option<String>,
+    pub min_amount: Threshold,
+    // When non-empty, only transfers where either side carries one of these
+    // entity labels (case-insensitive substring match) raise an alert.
+    pub labels: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AlertEvent {
+    pub rule: String,
+    pub tx_id: String,
+    pub height: String,
+    pub transaction_time: String,
+    pub from: String,
+    pub to: String,
+    pub from_label: Option<String>,
+    pub to_label: Option<String>,
+    pub token_contract_address: String,
+    pub symbol: String,
+    pub amount: f64,
+    pub amount_usd: Option<f64>,
+}
+
+struct Candidate {
+    tx_id: String,
+    height: String,
+    transaction_time: String,
+    from: String,
+    to: String,
+    token_contract_address: String,
+    symbol: String,
+    amount: String,
+}
+
+impl From<&TokenTransfer> for Candidate {
+    fn from(tx: &TokenTransfer) -> Self {
+        Candidate {
+            tx_id: tx.txid.clone(),
+            height: tx.height.clone(),
+            transaction_time: tx.transaction_time.clone(),
+            from: tx.from.clone(),
+            to: tx.to.clone(),
+            token_contract_address: tx.token_contract_address.clone(),
+            symbol: tx.transaction_symbol.clone(),
+            amount: tx.amount.clone(),
+        }
+    }
+}
+
+impl From<&LargeTransaction> for Candidate {
+    fn from(tx: &LargeTransaction) -> Self {
+        Candidate {
+            tx_id: tx.txid.clone(),
+            height: tx.height.clone(),
+            transaction_time: tx.transaction_time.clone(),
+            from: tx.from.clone(),
+            to: tx.to.clone(),
+            token_contract_address: tx.token_contract_address.clone(),
+            symbol: tx.transaction_symbol.clone(),
+            amount: tx.amount.clone(),
+        }
+    }
+}
+
+type AlertCallback = Box<dyn Fn(&AlertEvent) + Send + Sync>;
+
+pub struct AlertMonitor {
+    rules: Vec<AlertRule>,
+    prices: HashMap<String, f64>,
+    seen: HashSet<String>,
+    seen_order: VecDeque<String>,
+    seen_capacity: usize,
+    callbacks: Vec<AlertCallback>,
+}
+
+impl AlertMonitor {
+    pub fn new(rules: Vec<AlertRule>) -> Self {
+        AlertMonitor {
+            rules,
+            prices: HashMap::new(),
+            seen: HashSet::new(),
+            seen_order: VecDeque::new(),
+            seen_capacity: SEEN_CAPACITY,
+            callbacks: Vec::new(),
+        }
+    }
+
+    // Once more alerts than this have been delivered, the oldest are
+    // forgotten and could fire again if they reappear in a poll.
+    pub fn with_seen_capacity(mut self, capacity: usize) -> Self {
+        self.seen_capacity = capacity.max(1);
+        self
+    }
+
+    pub fn on_alert<F>(&mut self, callback: F)
+    where
+        F: Fn(&AlertEvent) + Send + Sync + 'static,
+    {
+        self.callbacks.push(Box::new(callback));
+    }
+
+    // Native KAIA has no contract address, so its price is keyed by "".
+    pub fn set_price(&mut self, token_contract_address: &str, price_usd: f64) {
+        self.prices
+            .insert(token_contract_address.to_lowercase(), price_usd);
+    }
+
+    // Native KAIA is priced through wrapped KAIA.
+    pub async fn refresh_price(
+        &mut self,
+        oklink: &Oklink,
+        token_contract_address: &str,
+    ) -> Result<Option<f64>, TransportError> {
+        let token = price_token(token_contract_address);
+        let prices = oklink.token_price(&[&token]).await?;
+        let price = prices
+            .data
+            .iter()
+            .find(|price| price.token_contract_address.eq_ignore_ascii_case(&token))
+            .and_then(|price| price.last_price.parse::<f64>().ok());
+        if let Some(price) = price {
+            self.set_price(token_contract_address, price);
+        }
+        Ok(price)
+    }
+
+    // Fetches current prices for every `Threshold::Usd` rule. A token the
+    // API has no price for keeps its last known or `set_price` value, and is
+    // an error if it has none.
+    async fn refresh_prices(&mut self, oklink: &Oklink) -> Result<(), AlertError> {
+        let mut tokens: Vec<String> = self
+            .rules
+            .iter()
+            .filter(|rule| matches!(rule.min_amount, Threshold::Usd(_)))
+            .map(|rule| {
+                rule.token_contract_address
+                    .as_deref()
+                    .unwrap_or("")
+                    .to_lowercase()
+            })
+            .collect();
+        tokens.sort_unstable();
+        tokens.dedup();
+        for token in tokens {
+            if self.refresh_price(oklink, &token).await?.is_none()
+                && !self.prices.contains_key(&token)
+            {
+                return Err(AlertError::MissingPrice(token));
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn poll(&mut self, oklink: &Oklink) -> Result<Vec<AlertEvent>, AlertError> {
+        self.refresh_prices(oklink).await?;
+        let mut events = Vec::new();
+        for rule in self.rules.clone() {
+            let candidates: Vec<Candidate> = match &rule.token_contract_address {
+                Some(token) => {
+                    let min_amount = match rule.min_amount {
+                        Threshold::Units(min) => Some(min.to_string()),
+                        Threshold::Usd(_) => None,
+                    };
+                    oklink
+                        .token_transfer_details(token, None, min_amount.as_deref(), None, None)
+                        .await?
+                        .data
+                        .transaction_list
+                        .iter()
+                        .map(Candidate::from)
+                        .collect()
+                }
+                None => oklink
+                    .large_transaction_list(None, None, None, None)
+                    .await?
+                    .data
+                    .transaction_list
+                    .iter()
+                    .map(Candidate::from)
+                    .collect(),
+            };
+            let passing: Vec<(Candidate, f64, Option<f64>)> = candidates
+                .into_iter()
+                .filter_map(|candidate| self.evaluate(&rule, candidate))
+                .collect();
+            let addresses: Vec<&str> = passing
+                .iter()
+                .flat_map(|(candidate, _, _)| [candidate.from.as_str(), candidate.to.as_str()])
+                .collect();
+            let labels = oklink.entity_labels(&addresses).await?;
+            for (candidate, amount, amount_usd) in passing {
+                if let Some(event) = self.label(&rule, &labels, candidate, amount, amount_usd) {
+                    for callback in &self.callbacks {
+                        callback(&event);
+                    }
+                    events.push(event);
+                }
+            }
+        }
+        Ok(events)
+    }
+
+    // Needs a Tokio runtime, so it is unavailable on wasm32. Poll failures
+    // are yielded as errors and retried on the next tick.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn watch(
+        mut self,
+        oklink: Arc<Oklink>,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<AlertEvent, AlertError>> {
+        let (tx, rx) = mpsc::channel(64);
+        tokio::spawn(async move {
+            loop {
+                let results = match self.poll(&oklink).await {
+                    Ok(events) => events.into_iter().map(Ok).collect(),
+                    Err(e) => vec![Err(e)],
+                };
+                for result in results {
+                    if tx.send(result).await.is_err() {
+                        return;
+                    }
+                }
+                tokio::time::sleep(interval).await;
+            }
+        });
+        ReceiverStream::new(rx)
+    }
+
+    fn key(rule: &AlertRule, candidate: &Candidate) -> String {
+        format!(
+            "{}:{}:{}:{}:{}",
+            rule.name, candidate.tx_id, candidate.from, candidate.to, candidate.amount
+        )
+    }
+
+    // Applies the dedup and amount checks, returning the parsed amount and
+    // its USD value for candidates that pass.
+    fn evaluate(
+        &self,
+        rule: &AlertRule,
+        candidate: Candidate,
+    ) -> Option<(Candidate, f64, Option<f64>)> {
+        if self.seen.contains(&Self::key(rule, &candidate)) {
+            return None;
+        }
+        let amount = candidate.amount.parse::<f64>().ok()?;
+        let price_key = rule
+            .token_contract_address
+            .as_deref()
+            .unwrap_or("")
+            .to_lowercase();
+        let amount_usd = self.prices.get(&price_key).map(|price| amount * price);
+        let passes = match rule.min_amount {
+            Threshold::Units(min) => amount >= min,
+            Threshold::Usd(min) => amount_usd.is_some_and(|usd| usd >= min),
+        };
+        passes.then_some((candidate, amount, amount_usd))
+    }
+
+    // Applies the label filter and records the alert as delivered.
+    fn label(
+        &mut self,
+        rule: &AlertRule,
+        labels: &HashMap<String, String>,
+        candidate: Candidate,
+        amount: f64,
+        amount_usd: Option<f64>,
+    ) -> Option<AlertEvent> {
+        let from_label = labels.get(&candidate.from.to_lowercase()).cloned();
+        let to_label = labels.get(&candidate.to.to_lowercase()).cloned();
+        if !rule.labels.is_empty() {
+            let matches = |label: &Option<String>| {
+                label.as_ref().is_some_and(|label| {
+                    let label = label.to_lowercase();
+                    rule.labels
+                        .iter()
+                        .any(|wanted| label.contains(&wanted.to_lowercase()))
+                })
+            };
+            if !matches(&from_label) && !matches(&to_label) {
+                return None;
+            }
+        }
+        self.remember(Self::key(rule, &candidate));
+        Some(AlertEvent {
+            rule: rule.name.clone(),
+            tx_id: candidate.tx_id,
+            height: candidate.height,
+            transaction_time: candidate.transaction_time,
+            from: candidate.from,
+            to: candidate.to,
+            from_label,
+            to_label,
+            token_contract_address: candidate.token_contract_address,
+            symbol: candidate.symbol,
+            amount,
+            amount_usd,
+        })
+    }
+
+    fn remember(&mut self, key: String) {
+        if self.seen.insert(key.clone()) {
+            self.seen_order.push_back(key);
+        }
+        while self.seen_order.len() > self.seen_capacity {
+            if let Some(oldest) = self.seen_order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::oklink::{Oklink, TokenBalance, TokenTransaction, TokenTransfer, TokenTransferList};
+use crate::transport::TransportError;
+
+// 10^38 is the largest power of ten that fits in a u128.
+const MAX_DECIMALS: u32 = 38;
+
+#[derive(Debug)]
+pub enum AmountError {
+    Http(TransportError),
+    Invalid(String),
+    TooPrecise { value: String, decimals: u32 },
+    Overflow(String),
+    UnknownToken(String),
+}
+
+impl From<TransportError> for AmountError {
+    fn from(e: TransportError) -> Self {
+        AmountError::Http(e)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenMeta {
+    pub decimals: u32,
+    pub symbol: String,
+}
+
+// An amount stored as the raw on-chain integer together with the token's
+// decimals, so conversions never go through floating point.
+//
+// The raw value is a u128 rather than the chain's uint256: amounts above
+// u128::MAX (about 3.4e20 whole tokens at 18 decimals) are rejected with
+// `AmountError::Overflow` instead of being truncated.
+//
+// Amounts only compare equal or ordered when decimals and symbol both match.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TokenAmount {
+    raw: u128,
+    decimals: u32,
+    symbol: Option<String>,
+}
+
+fn scale(decimals: u32) -> Result<u128, AmountError> {
+    if decimals > MAX_DECIMALS {
+        return Err(AmountError::Overflow(format!("{} decimals", decimals)));
+    }
+    Ok(10u128.pow(decimals))
+}
+
+impl TokenAmount {
+    pub fn from_raw(raw: u128, decimals: u32) -> Result<Self, AmountError> {
+        scale(decimals)?;
+        Ok(TokenAmount {
+            raw,
+            decimals,
+            symbol: None,
+        })
+    }
+
+    pub fn from_raw_str(raw: &str, decimals: u32) -> Result<Self, AmountError> {
+        let trimmed = raw.trim();
+        if trimmed.is_empty() || !trimmed.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(AmountError::Invalid(raw.to_string()));
+        }
+        let raw = trimmed
+            .parse::<u128>()
+            .map_err(|_| AmountError::Overflow(raw.to_string()))?;
+        TokenAmount::from_raw(raw, decimals)
+    }
+
+    // Parses a human-readable amount such as "1.25". Digits beyond the token's
+    // decimals are rejected unless they are zeros.
+    pub fn from_units(value: &str, decimals: u32) -> Result<Self, AmountError> {
+        let trimmed = value.trim();
+        let (int_part, frac_part) = trimmed.split_once('.').unwrap_or((trimmed, ""));
+        let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        if (int_part.is_empty() && frac_part.is_empty())
+            || !is_digits(int_part)
+            || !is_digits(frac_part)
+        {
+            return Err(AmountError::Invalid(value.to_string()));
+        }
+        let frac_part = if frac_part.len() > decimals as usize {
+            let (kept, dropped) = frac_part.split_at(decimals as usize);
+            if dropped.bytes().any(|b| b != b'0') {
+                return Err(AmountError::TooPrecise {
+                    value: value.to_string(),
+                    decimals,
+                });
+            }
+            kept
+        } else {
+            frac_part
+        };
+        let overflow = || AmountError::Overflow(value.to_string());
+        let int_value = if int_part.is_empty() {
+            0
+        } else {
+            int_part.parse::<u128>().map_err(|_| overflow())?
+        };
+        let frac_value = if frac_part.is_empty() {
+            0
+        } else {
+            frac_part.parse::<u128>().map_err(|_| overflow())?
+                * scale(decimals - frac_part.len() as u32)?
+        };
+        let raw = int_value
+            .checked_mul(scale(decimals)?)
+            .and_then(|v| v.checked_add(frac_value))
+            .ok_or_else(overflow)?;
+        TokenAmount::from_raw(raw, decimals)
+    }
+
+    pub fn with_symbol(mut self, symbol: &str) -> Self {
+        self.symbol = Some(symbol.to_string()).filter(|s| !s.is_empty());
+        self
+    }
+
+    pub fn raw(&self) -> u128 {
+        self.raw
+    }
+
+    pub fn decimals(&self) -> u32 {
+        self.decimals
+    }
+
+    pub fn symbol(&self) -> Option<&str> {
+        self.symbol.as_deref()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.raw == 0
+    }
+
+    // Exact decimal representation without trailing zeros, e.g. "1.5".
+    pub fn to_units_string(&self) -> String {
+        let scale = 10u128.pow(self.decimals);
+        let int_value = self.raw / scale;
+        let frac_value = self.raw % scale;
+        if frac_value == 0 {
+            return int_value.to_string();
+        }
+        let frac = format!("{:0width$}", frac_value, width = self.decimals as usize);
+        format!("{}.{}", int_value, frac.trim_end_matches('0'))
+    }
+
+    // Lossy; meant for display and USD valuation, never for accounting.
+    pub fn to_f64(&self) -> f64 {
+        self.raw as f64 / 10f64.powi(self.decimals as i32)
+    }
+
+    fn same_unit(&self, other: &TokenAmount) -> bool {
+        self.decimals == other.decimals && self.symbol == other.symbol
+    }
+
+    pub fn checked_add(&self, other: &TokenAmount) -> Option<TokenAmount> {
+        if !self.same_unit(other) {
+            return None;
+        }
+        Some(TokenAmount {
+            raw: self.raw.checked_add(other.raw)?,
+            decimals: self.decimals,
+            symbol: self.symbol.clone(),
+        })
+    }
+
+    pub fn checked_sub(&self, other: &TokenAmount) -> Option<TokenAmount> {
+        if !self.same_unit(other) {
+            return None;
+        }
+        Some(TokenAmount {
+            raw: self.raw.checked_sub(other.raw)?,
+            decimals: self.decimals,
+            symbol: self.symbol.clone(),
+        })
+    }
+}
+
+impl PartialOrd for TokenAmount {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self.same_unit(other) {
+            Some(self.raw.cmp(&other.raw))
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for TokenAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.symbol {
+            Some(symbol) => write!(f, "{} {}", self.to_units_string(), symbol),
+            None => write!(f, "{}", self.to_units_string()),
+        }
+    }
+}
+
+impl TokenTransaction {
+    pub fn token_amount(&self, decimals: u32) -> Result<TokenAmount, AmountError> {
+        Ok(TokenAmount::from_units(&self.amount, decimals)?.with_symbol(&self.symbol))
+    }
+}
+
+impl TokenTransfer {
+    pub fn token_amount(&self, decimals: u32) -> Result<TokenAmount, AmountError> {
+        Ok(TokenAmount::from_units(&self.amount, decimals)?.with_symbol(&self.transaction_symbol))
+    }
+}
+
+impl TokenBalance {
+    pub fn token_amount(&self, decimals: u32) -> Result<TokenAmount, AmountError> {
+        Ok(TokenAmount::from_units(&self.holding_amount, decimals)?.with_symbol(&self.symbol))
+    }
+}
+
+impl Oklink {
+    // Decimals and symbol come from `token_list` and are cached for the
+    // lifetime of the client.
+    pub async fn token_meta(&self, token_contract_address: &str) -> Result<TokenMeta, AmountError> {
+        let key = token_contract_address.to_lowercase();
+        if let Some(meta) = self.token_meta.lock().unwrap().get(&key) {
+            return Ok(meta.clone());
+        }
+        let list = self
+            .token_list(
+                None,
+                Some(token_contract_address),
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await?;
+        // The endpoint may return other tokens, e.g. when it ignores an
+        // address it doesn't know.
+        let info = list
+            .data
+            .token_list
+            .into_iter()
+            .find(|info| {
+                info.token_contract_address
+                    .eq_ignore_ascii_case(token_contract_address)
+            })
+            .ok_or_else(|| AmountError::UnknownToken(token_contract_address.to_string()))?;
+        let meta = TokenMeta {
+            decimals: info
+                .precision
+                .parse()
+                .map_err(|_| AmountError::Invalid(info.precision.clone()))?,
+            symbol: info.token,
+        };
+        self.token_meta.lock().unwrap().insert(key, meta.clone());
+        Ok(meta)
+    }
+
+    pub async fn token_amount(
+        &self,
+        token_contract_address: &str,
+        units: &str,
+    ) -> Result<TokenAmount, AmountError> {
+        let meta = self.token_meta(token_contract_address).await?;
+        Ok(TokenAmount::from_units(units, meta.decimals)?.with_symbol(&meta.symbol))
+    }
+
+    pub async fn token_amount_from_raw(
+        &self,
+        token_contract_address: &str,
+        raw: &str,
+    ) -> Result<TokenAmount, AmountError> {
+        let meta = self.token_meta(token_contract_address).await?;
+        Ok(TokenAmount::from_raw_str(raw, meta.decimals)?.with_symbol(&meta.symbol))
+    }
+
+    pub async fn token_transfer_details_between(
+        &self,
+        token_contract_address: &str,
+        min_amount: Option<&TokenAmount>,
+        max_amount: Option<&TokenAmount>,
+        page: Option<&str>,
+        limit: Option<&str>,
+    ) -> Result<TokenTransferList, TransportError> {
+        let min_amount = min_amount.map(TokenAmount::to_units_string);
+        let max_amount = max_amount.map(TokenAmount::to_units_string);
+        self.token_transfer_details(
+            token_contract_address,
+            max_amount.as_deref(),
+            min_amount.as_deref(),
+            page,
+            limit,
+        )
+        .await
+    }
+}
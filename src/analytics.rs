@@ -0,0 +1,264 @@
+use serde::Serialize;
+
+use crate::labels::{matches_keywords, EXCHANGE_KEYWORDS};
+use crate::oklink::{Oklink, TokenPosition};
+use crate::transport::TransportError;
+
+const PAGE_LIMIT: &str = "100";
+
+#[derive(Debug, Clone)]
+pub struct DistributionOptions {
+    pub top_n: Vec<usize>,
+    // Lower bounds of the balance buckets, in token units.
+    pub bucket_bounds: Vec<f64>,
+    // Only the largest holders are looked up in `entity_labels`.
+    pub label_top_n: usize,
+    pub exchange_keywords: Vec<String>,
+    pub max_holders: Option<usize>,
+}
+
+impl Default for DistributionOptions {
+    fn default() -> Self {
+        DistributionOptions {
+            top_n: vec![10, 50, 100],
+            bucket_bounds: vec![0.0, 1.0, 100.0, 10_000.0, 1_000_000.0],
+            label_top_n: 100,
+            exchange_keywords: EXCHANGE_KEYWORDS.iter().map(|k| k.to_string()).collect(),
+            max_holders: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HolderDistribution {
+    pub token_contract_address: String,
+    pub holder_count: usize,
+    pub total_held: f64,
+    pub circulating_supply: Option<f64>,
+    pub top_concentration: Vec<TopConcentration>,
+    pub gini: f64,
+    pub nakamoto_coefficient: usize,
+    pub buckets: Vec<HolderBucket>,
+    pub exchange_share: f64,
+    pub exchange_holders: Vec<LabeledHolder>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TopConcentration {
+    pub top_n: usize,
+    pub share: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HolderBucket {
+    pub min: f64,
+    pub max: Option<f64>,
+    pub holders: usize,
+    pub amount: f64,
+    pub share: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LabeledHolder {
+    pub address: String,
+    pub label: String,
+    pub amount: f64,
+    pub share: f64,
+}
+
+impl HolderDistribution {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+// Balances must be sorted in descending order.
+pub fn gini(balances: &[f64]) -> f64 {
+    let n = balances.len() as f64;
+    let total: f64 = balances.iter().sum();
+    if balances.is_empty() || total <= 0.0 {
+        return 0.0;
+    }
+    let weighted: f64 = balances
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, b)| (i as f64 + 1.0) * b)
+        .sum();
+    (2.0 * weighted) / (n * total) - (n + 1.0) / n
+}
+
+// Smallest number of holders that together control more than half of the supply.
+pub fn nakamoto_coefficient(balances: &[f64]) -> usize {
+    let total: f64 = balances.iter().sum();
+    let mut held = 0.0;
+    for (i, balance) in balances.iter().enumerate() {
+        held += balance;
+        if held > total / 2.0 {
+            return i + 1;
+        }
+    }
+    balances.len()
+}
+
+pub fn compute_distribution(
+    token_contract_address: &str,
+    holders: &[(String, f64)],
+    circulating_supply: Option<f64>,
+    labels: &[(String, String)],
+    options: &DistributionOptions,
+) -> HolderDistribution {
+    let mut balances: Vec<f64> = holders.iter().map(|(_, amount)| *amount).collect();
+    balances.sort_by(|a, b| b.total_cmp(a));
+    let total_held: f64 = balances.iter().sum();
+    let share = |amount: f64| {
+        if total_held > 0.0 {
+            amount / total_held
+        } else {
+            0.0
+        }
+    };
+
+    let top_concentration = options
+        .top_n
+        .iter()
+        .map(|&top_n| TopConcentration {
+            top_n,
+            share: share(balances.iter().take(top_n).sum()),
+        })
+        .collect();
+
+    let mut bounds = options.bucket_bounds.clone();
+    bounds.sort_by(|a, b| a.total_cmp(b));
+    let buckets = bounds
+        .iter()
+        .enumerate()
+        .map(|(i, &min)| {
+            let max = bounds.get(i + 1).copied();
+            let in_bucket: Vec<f64> = balances
+                .iter()
+                .copied()
+                .filter(|&b| b >= min && max.is_none_or(|max| b < max))
+                .collect();
+            let amount = in_bucket.iter().sum();
+            HolderBucket {
+                min,
+                max,
+                holders: in_bucket.len(),
+                amount,
+                share: share(amount),
+            }
+        })
+        .collect();
+
+    let exchange_holders: Vec<LabeledHolder> = labels
+        .iter()
+        .filter(|(_, label)| matches_keywords(label, &options.exchange_keywords))
+        .filter_map(|(address, label)| {
+            holders
+                .iter()
+                .find(|(holder, _)| holder.eq_ignore_ascii_case(address))
+                .map(|(_, amount)| LabeledHolder {
+                    address: address.clone(),
+                    label: label.clone(),
+                    amount: *amount,
+                    share: share(*amount),
+                })
+        })
+        .collect();
+
+    HolderDistribution {
+        token_contract_address: token_contract_address.to_string(),
+        holder_count: balances.len(),
+        total_held,
+        circulating_supply,
+        top_concentration,
+        gini: gini(&balances),
+        nakamoto_coefficient: nakamoto_coefficient(&balances),
+        buckets,
+        exchange_share: exchange_holders.iter().map(|h| h.share).sum(),
+        exchange_holders,
+    }
+}
+
+impl Oklink {
+    pub async fn holder_distribution(
+        &self,
+        token_contract_address: &str,
+        options: &DistributionOptions,
+    ) -> Result<HolderDistribution, TransportError> {
+        let mut holders: Vec<(String, f64)> = Vec::new();
+        let mut circulating_supply = None;
+        let mut page = 1u32;
+        loop {
+            let page_str = page.to_string();
+            let response = self
+                .token_position_list(
+                    token_contract_address,
+                    None,
+                    Some(&page_str),
+                    Some(PAGE_LIMIT),
+                )
+                .await?;
+            circulating_supply =
+                circulating_supply.or_else(|| response.data.circulating_supply.parse::<f64>().ok());
+            holders.extend(response.data.position_list.into_iter().filter_map(|p| {
+                p.amount
+                    .parse::<f64>()
+                    .ok()
+                    .map(|amount| (p.holder_address, amount))
+            }));
+            let total_page = response.data.total_page.parse::<u32>().unwrap_or(1);
+            let reached_max = options.max_holders.is_some_and(|max| holders.len() >= max);
+            if page >= total_page || reached_max {
+                break;
+            }
+            page += 1;
+        }
+        if let Some(max) = options.max_holders {
+            holders.truncate(max);
+        }
+
+        let mut largest = holders.clone();
+        largest.sort_by(|a, b| b.1.total_cmp(&a.1));
+        largest.truncate(options.label_top_n);
+        let addresses: Vec<&str> = largest
+            .iter()
+            .map(|(address, _)| address.as_str())
+            .collect();
+        let found = self.entity_labels(&addresses).await?;
+        let labels: Vec<(String, String)> = largest
+            .into_iter()
+            .filter_map(|(address, _)| {
+                found
+                    .get(&address.to_lowercase())
+                    .cloned()
+                    .map(|label| (address, label))
+            })
+            .collect();
+
+        Ok(compute_distribution(
+            token_contract_address,
+            &holders,
+            circulating_supply,
+            &labels,
+            options,
+        ))
+    }
+
+    // A single holder's position in the token, including its rank.
+    pub async fn holder_position(
+        &self,
+        token_contract_address: &str,
+        holder_address: &str,
+    ) -> Result<Option<TokenPosition>, TransportError> {
+        let response = self
+            .token_position_statistics(token_contract_address, Some(holder_address), None, None)
+            .await?;
+        Ok(response
+            .data
+            .position_list
+            .into_iter()
+            .find(|position| position.holder_address.eq_ignore_ascii_case(holder_address)))
+    }
+}
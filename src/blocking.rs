@@ -0,0 +1,472 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+use futures::StreamExt;
+use tokio::runtime::{Builder, Runtime};
+
+use crate::amount::{AmountError, TokenAmount, TokenMeta};
+use crate::analytics::{DistributionOptions, HolderDistribution};
+use crate::feed::{Activity, FeedCursor};
+use crate::fees::{FeeError, FeeReport};
+use crate::graph::{TraceOptions, TransferGraph};
+use crate::kaia::{AccountError, AccountKey, KaiaAccount};
+use crate::keys::{KeyPool, KeyStats};
+use crate::labels::{HasAddresses, Labeled};
+use crate::middleware::Middleware;
+use crate::nft::{NftError, NftHolding, NftTransfer};
+use crate::oklink::{
+    AddressBalanceHistory, AddressInformation, AddressTokenBalance, BatchAddressBalances,
+    BatchAddressTokenBalances, BatchInternalTransactionList, BatchNormalTransactionList,
+    BatchTokenTransactionList, BatchTransactionDetails, BlockTransactionList, EntityLabels,
+    InternalTransactionList, LargeTransactionList, NativeTokenRanking, NftCollectionInfo,
+    NftDetails, NftInventory, NftTransactionList, NormalTransactionList, RichList, TokenBalance,
+    TokenList, TokenPosition, TokenPositionList, TokenPriceHistory, TokenPriceList,
+    TokenSupplyHistory, TokenTransaction, TokenTransactionList, TokenTransferList,
+    TransactionDetails,
+};
+use crate::portfolio::{HistoricalPortfolio, Portfolio};
+use crate::ranking::{RankingKind, RankingSnapshot, SnapshotError};
+use crate::transport::{HttpTransport, TransportError};
+use crate::types::ProtocolType;
+use crate::valuation::{ValuedBalance, ValuedTransfer};
+
+// Synchronous wrapper around the async client. Every call runs the async
+// method to completion on a runtime owned by the client, so request building,
+// response models and error types are shared. Must not be used from within
+// an async context.
+pub struct Oklink {
+    inner: crate::oklink::Oklink,
+    runtime: Runtime,
+}
+
+macro_rules! blocking {
+    ($(fn $name:ident(&self $(, $arg:ident: $ty:ty)* $(,)?) -> $ret:ty;)*) => {
+        $(
+            // Mirrors the async signature, some of which take many arguments.
+            #[allow(clippy::too_many_arguments)]
+            pub fn $name(&self $(, $arg: $ty)*) -> $ret {
+                self.runtime.block_on(self.inner.$name($($arg),*))
+            }
+        )*
+    };
+}
+
+impl Oklink {
+    pub fn new(api_key: impl Into<KeyPool>) -> Self {
+        Oklink::from_async(crate::oklink::Oklink::new(api_key))
+    }
+
+    pub fn with_transport(
+        api_key: impl Into<KeyPool>,
+        transport: impl HttpTransport + 'static,
+    ) -> Self {
+        Oklink::from_async(crate::oklink::Oklink::with_transport(api_key, transport))
+    }
+
+    pub fn from_async(inner: crate::oklink::Oklink) -> Self {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to build tokio runtime");
+        Oklink { inner, runtime }
+    }
+
+    pub fn as_async(&self) -> &crate::oklink::Oklink {
+        &self.inner
+    }
+
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.inner = self.inner.with_base_url(base_url);
+        self
+    }
+
+    pub fn with_middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.inner = self.inner.with_middleware(middleware);
+        self
+    }
+
+    pub fn with_single_flight(mut self, enabled: bool) -> Self {
+        self.inner = self.inner.with_single_flight(enabled);
+        self
+    }
+
+    // A request waiting for a rate-limited key blocks the calling thread.
+    pub fn with_max_rate_limit_wait(mut self, max_wait: Duration) -> Self {
+        self.inner = self.inner.with_max_rate_limit_wait(max_wait);
+        self
+    }
+
+    pub fn key_stats(&self) -> Vec<KeyStats> {
+        self.inner.key_stats()
+    }
+
+    // Pages are fetched as the iterator is advanced.
+    pub fn activity_feed<'a>(
+        &'a self,
+        address: &'a str,
+        cursor: Option<FeedCursor>,
+    ) -> impl Iterator<Item = Result<Activity, TransportError>> + 'a {
+        let mut feed = Box::pin(self.inner.activity_feed(address, cursor));
+        std::iter::from_fn(move || self.runtime.block_on(feed.next()))
+    }
+
+    pub fn with_labels<T: HasAddresses>(&self, response: T) -> Result<Labeled<T>, TransportError> {
+        self.runtime.block_on(self.inner.with_labels(response))
+    }
+
+    blocking! {
+        fn address_info(&self, address: &str) -> Result<AddressInformation, TransportError>;
+        fn evm_address_info(&self, address: &str) -> Result<AddressInformation, TransportError>;
+        fn address_active_chain(&self, address: &str) -> Result<AddressInformation, TransportError>;
+        fn address_token_balance(
+            &self,
+            address: &str,
+            protocol_type: ProtocolType,
+            token_contract_address: Option<&str>,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<AddressTokenBalance, TransportError>;
+        fn address_balance_details(
+            &self,
+            address: &str,
+            protocol_type: &str,
+            token_contract_address: Option<&str>,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<NftInventory, TransportError>;
+        fn address_balance_history(
+            &self,
+            address: &str,
+            height: &str,
+            token_contract_address: Option<&str>,
+        ) -> Result<AddressBalanceHistory, TransportError>;
+        fn address_transaction_list(
+            &self,
+            address: &str,
+            protocol_type: Option<&str>,
+            symbol: Option<&str>,
+            start_block_height: Option<&str>,
+            end_block_height: Option<&str>,
+            is_from_or_to: Option<&str>,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<AddressInformation, TransportError>;
+        fn address_normal_transaction_list(
+            &self,
+            address: &str,
+            start_block_height: Option<&str>,
+            end_block_height: Option<&str>,
+            is_from_or_to: Option<&str>,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<NormalTransactionList, TransportError>;
+        fn address_internal_transaction_list(
+            &self,
+            address: &str,
+            start_block_height: Option<&str>,
+            end_block_height: Option<&str>,
+            is_from_or_to: Option<&str>,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<InternalTransactionList, TransportError>;
+        fn address_token_transaction_list(
+            &self,
+            address: &str,
+            protocol_type: &str,
+            token_contract_address: Option<&str>,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<TokenTransactionList, TransportError>;
+        fn address_token_transaction_list_by_height(
+            &self,
+            address: &str,
+            protocol_type: &str,
+            token_contract_address: Option<&str>,
+            start_block_height: Option<&str>,
+            end_block_height: Option<&str>,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<TokenTransactionList, TransportError>;
+        fn address_entity_labels(&self, address: &str) -> Result<EntityLabels, TransportError>;
+        fn batch_address_entity_labels(
+            &self,
+            addresses: &[&str],
+        ) -> Result<EntityLabels, TransportError>;
+        fn batch_address_balances(
+            &self,
+            addresses: &[&str],
+        ) -> Result<BatchAddressBalances, TransportError>;
+        fn batch_address_token_balances(
+            &self,
+            addresses: &[&str],
+            protocol_type: Option<&str>,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<BatchAddressTokenBalances, TransportError>;
+        fn batch_address_normal_transaction_list(
+            &self,
+            addresses: &[&str],
+            start_block_height: Option<&str>,
+            end_block_height: Option<&str>,
+            is_from_or_to: Option<&str>,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<BatchNormalTransactionList, TransportError>;
+        fn batch_address_internal_transaction_list(
+            &self,
+            addresses: &[&str],
+            start_block_height: Option<&str>,
+            end_block_height: Option<&str>,
+            is_from_or_to: Option<&str>,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<BatchInternalTransactionList, TransportError>;
+        fn batch_address_token_transaction_list(
+            &self,
+            addresses: &[&str],
+            start_block_height: &str,
+            end_block_height: &str,
+            page: Option<&str>,
+            limit: Option<&str>,
+            protocol_type: Option<&str>,
+            token_contract_address: Option<&str>,
+            is_from_or_to: Option<&str>,
+        ) -> Result<BatchTokenTransactionList, TransportError>;
+        fn rich_list(&self, address: Option<&str>) -> Result<RichList, TransportError>;
+        fn native_token_ranking(
+            &self,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<NativeTokenRanking, TransportError>;
+        fn transaction_list(
+            &self,
+            block_hash: Option<&str>,
+            height: Option<&str>,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<BlockTransactionList, TransportError>;
+        fn large_transaction_list(
+            &self,
+            transaction_type: Option<&str>,
+            height: Option<&str>,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<LargeTransactionList, TransportError>;
+        fn unconfirmed_transaction_list(
+            &self,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<AddressInformation, TransportError>;
+        fn internal_transaction_details(
+            &self,
+            tx_id: &str,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<AddressInformation, TransportError>;
+        fn token_transaction_details(
+            &self,
+            tx_id: &str,
+            protocol_type: &str,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<AddressInformation, TransportError>;
+        fn transaction_details(&self, tx_id: &str) -> Result<TransactionDetails, TransportError>;
+        fn batch_transaction_details(
+            &self,
+            tx_ids: &[&str],
+        ) -> Result<BatchTransactionDetails, TransportError>;
+        fn batch_internal_transaction_details(
+            &self,
+            tx_ids: &[&str],
+        ) -> Result<AddressInformation, TransportError>;
+        fn batch_token_transaction_details(
+            &self,
+            tx_ids: &[&str],
+            protocol_type: Option<&str>,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<AddressInformation, TransportError>;
+        fn token_list(
+            &self,
+            protocol_type: Option<&str>,
+            token_contract_address: Option<&str>,
+            start_time: Option<&str>,
+            end_time: Option<&str>,
+            order_by: Option<&str>,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<TokenList, TransportError>;
+        fn token_position_list(
+            &self,
+            token_contract_address: &str,
+            holder_address: Option<&str>,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<TokenPositionList, TransportError>;
+        fn token_position_statistics(
+            &self,
+            token_contract_address: &str,
+            holder_address: Option<&str>,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<TokenPositionList, TransportError>;
+        fn token_transfer_details(
+            &self,
+            token_contract_address: &str,
+            max_amount: Option<&str>,
+            min_amount: Option<&str>,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<TokenTransferList, TransportError>;
+        fn batch_token_transaction(
+            &self,
+            token_contract_address: &str,
+            start_block_height: &str,
+            end_block_height: &str,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<AddressInformation, TransportError>;
+        fn token_supply_history(
+            &self,
+            token_contract_address: &str,
+            height: &str,
+        ) -> Result<TokenSupplyHistory, TransportError>;
+        fn token_transaction_statistics(
+            &self,
+            token_contract_address: &str,
+            order_by: Option<&str>,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<AddressInformation, TransportError>;
+        fn nft_transaction_list(
+            &self,
+            token_contract_address: &str,
+            token_id: Option<&str>,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<NftTransactionList, TransportError>;
+        fn nft_collection_info(
+            &self,
+            token_contract_address: &str,
+        ) -> Result<NftCollectionInfo, TransportError>;
+        fn nft_details(
+            &self,
+            token_contract_address: &str,
+            token_id: &str,
+        ) -> Result<NftDetails, TransportError>;
+        fn token_price(
+            &self,
+            token_contract_addresses: &[&str],
+        ) -> Result<TokenPriceList, TransportError>;
+        fn token_price_history(
+            &self,
+            token_contract_address: &str,
+            period: Option<&str>,
+            after: Option<&str>,
+            before: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<TokenPriceHistory, TransportError>;
+        fn token_meta(&self, token_contract_address: &str) -> Result<TokenMeta, AmountError>;
+        fn token_amount(
+            &self,
+            token_contract_address: &str,
+            units: &str,
+        ) -> Result<TokenAmount, AmountError>;
+        fn token_amount_from_raw(
+            &self,
+            token_contract_address: &str,
+            raw: &str,
+        ) -> Result<TokenAmount, AmountError>;
+        fn token_transfer_details_between(
+            &self,
+            token_contract_address: &str,
+            min_amount: Option<&TokenAmount>,
+            max_amount: Option<&TokenAmount>,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<TokenTransferList, TransportError>;
+        fn holder_distribution(
+            &self,
+            token_contract_address: &str,
+            options: &DistributionOptions,
+        ) -> Result<HolderDistribution, TransportError>;
+        fn holder_position(
+            &self,
+            token_contract_address: &str,
+            holder_address: &str,
+        ) -> Result<Option<TokenPosition>, TransportError>;
+        fn fee_report(
+            &self,
+            address: &str,
+            start_block_height: Option<&str>,
+            end_block_height: Option<&str>,
+        ) -> Result<FeeReport, FeeError>;
+        fn trace_funds(
+            &self,
+            seed: &str,
+            options: &TraceOptions,
+        ) -> Result<TransferGraph, TransportError>;
+        fn account_key(
+            &self,
+            address: &str,
+            rpc_url: &str,
+        ) -> Result<Option<AccountKey>, AccountError>;
+        fn account(&self, address: &str, rpc_url: &str) -> Result<KaiaAccount, AccountError>;
+        fn entity_labels(
+            &self,
+            addresses: &[&str],
+        ) -> Result<HashMap<String, String>, TransportError>;
+        fn nft_holdings(
+            &self,
+            address: &str,
+            protocol_type: ProtocolType,
+            token_contract_address: Option<&str>,
+        ) -> Result<Vec<NftHolding>, NftError>;
+        fn nft_token_history(
+            &self,
+            token_contract_address: &str,
+            token_id: &str,
+        ) -> Result<Vec<NftTransfer>, NftError>;
+        fn portfolio(&self, address: &str) -> Result<Portfolio, TransportError>;
+        fn portfolios(&self, addresses: &[&str]) -> Result<Vec<Portfolio>, TransportError>;
+        fn portfolio_at(
+            &self,
+            address: &str,
+            height: &str,
+        ) -> Result<HistoricalPortfolio, TransportError>;
+        fn portfolio_at_with_tokens(
+            &self,
+            address: &str,
+            height: &str,
+            token_contract_addresses: &[&str],
+        ) -> Result<HistoricalPortfolio, TransportError>;
+        fn ranking_snapshot(&self, kind: RankingKind) -> Result<RankingSnapshot, TransportError>;
+        fn save_ranking_snapshot(
+            &self,
+            kind: RankingKind,
+            path: impl AsRef<Path>,
+        ) -> Result<RankingSnapshot, SnapshotError>;
+        fn price_at(
+            &self,
+            token_contract_address: &str,
+            timestamp_ms: u64,
+        ) -> Result<Option<f64>, TransportError>;
+        fn value_transfers(
+            &self,
+            transfers: Vec<TokenTransaction>,
+        ) -> Result<Vec<ValuedTransfer>, TransportError>;
+        fn value_balances(
+            &self,
+            balances: Vec<TokenBalance>,
+        ) -> Result<Vec<ValuedBalance>, TransportError>;
+        fn valued_token_transaction_list(
+            &self,
+            address: &str,
+            protocol_type: &str,
+            token_contract_address: Option<&str>,
+            page: Option<&str>,
+            limit: Option<&str>,
+        ) -> Result<Vec<ValuedTransfer>, TransportError>;
+    }
+}
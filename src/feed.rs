@@ -0,0 +1,276 @@
+use futures::future;
+use futures::stream::{self, Stream};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+
+use crate::oklink::Oklink;
+use crate::transfer::{Transfer, TransferKind};
+use crate::transport::TransportError;
+use crate::types::ProtocolType;
+
+const PAGE_LIMIT: &str = "50";
+
+// Position in an activity feed: the last entry a consumer has seen. Entries
+// are ordered by height, newest first. The list endpoints don't say where a
+// transaction sits in its block, so transactions in the same block are
+// ordered by descending hash rather than by execution order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeedCursor {
+    pub height: u64,
+    pub tx_hash: String,
+}
+
+// Every transfer an address took part in within one transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Activity {
+    pub tx_hash: String,
+    pub height: u64,
+    // Unix time in milliseconds, if any of the transfers reported it.
+    pub time: Option<u64>,
+    pub transfers: Vec<Transfer>,
+}
+
+impl Activity {
+    pub fn cursor(&self) -> FeedCursor {
+        FeedCursor {
+            height: self.height,
+            tx_hash: self.tx_hash.clone(),
+        }
+    }
+
+    fn is_after(&self, cursor: &FeedCursor) -> bool {
+        (self.height, self.tx_hash.as_str()) < (cursor.height, cursor.tx_hash.as_str())
+    }
+}
+
+// One of the transaction list endpoints, read page by page.
+struct FeedSource {
+    kind: TransferKind,
+    // Set for token sources.
+    protocol_type: Option<ProtocolType>,
+    page: u32,
+    done: bool,
+    // Transfers keyed by their height, newest first.
+    buffer: VecDeque<(u64, Transfer)>,
+}
+
+impl FeedSource {
+    fn new(kind: TransferKind) -> Self {
+        FeedSource {
+            kind,
+            protocol_type: None,
+            page: 1,
+            done: false,
+            buffer: VecDeque::new(),
+        }
+    }
+
+    fn token(protocol_type: ProtocolType) -> Self {
+        FeedSource {
+            protocol_type: Some(protocol_type),
+            ..FeedSource::new(TransferKind::Token)
+        }
+    }
+
+    fn needs_page(&self) -> bool {
+        self.buffer.is_empty() && !self.done
+    }
+}
+
+struct FeedState<'a> {
+    oklink: &'a Oklink,
+    address: &'a str,
+    cursor: Option<FeedCursor>,
+    sources: Vec<FeedSource>,
+    ready: VecDeque<Activity>,
+}
+
+impl FeedState<'_> {
+    // Fetches the next page of every source that has run dry, concurrently.
+    async fn refill(&mut self) -> Result<(), TransportError> {
+        // Every list can skip blocks newer than the cursor.
+        let end_height = self.cursor.as_ref().map(|cursor| cursor.height.to_string());
+        let wanted: Vec<usize> = (0..self.sources.len())
+            .filter(|i| self.sources[*i].needs_page())
+            .collect();
+        let pages = future::join_all(wanted.iter().map(|i| {
+            let source = &self.sources[*i];
+            self.oklink.feed_page(
+                source.kind,
+                source.protocol_type,
+                self.address,
+                source.page,
+                end_height.as_deref(),
+            )
+        }))
+        .await;
+        for (i, page) in wanted.into_iter().zip(pages) {
+            let (transfers, total_page) = page?;
+            let source = &mut self.sources[i];
+            source.done = transfers.is_empty() || source.page >= total_page;
+            // A transfer without a height can't be placed in the feed.
+            let mut transfers: Vec<(u64, Transfer)> = transfers
+                .into_iter()
+                .filter_map(|transfer| Some((transfer.height?, transfer)))
+                .collect();
+            transfers.sort_by_key(|(height, _)| Reverse(*height));
+            source.page += 1;
+            source.buffer.extend(transfers);
+        }
+        Ok(())
+    }
+
+    async fn next(&mut self) -> Result<Option<Activity>, TransportError> {
+        loop {
+            if let Some(activity) = self.ready.pop_front() {
+                return Ok(Some(activity));
+            }
+            self.refill().await?;
+            let Some(height) = self
+                .sources
+                .iter()
+                .filter_map(|source| source.buffer.front())
+                .map(|(height, _)| *height)
+                .max()
+            else {
+                return Ok(None);
+            };
+
+            // A block can straddle a page boundary, so keep paging until every
+            // source has moved past it.
+            let mut transfers = Vec::new();
+            loop {
+                for source in &mut self.sources {
+                    while source
+                        .buffer
+                        .front()
+                        .is_some_and(|(transfer_height, _)| *transfer_height == height)
+                    {
+                        transfers.extend(source.buffer.pop_front().map(|(_, transfer)| transfer));
+                    }
+                }
+                if !self.sources.iter().any(FeedSource::needs_page) {
+                    break;
+                }
+                self.refill().await?;
+            }
+
+            let mut block: Vec<Activity> = Vec::new();
+            for transfer in transfers {
+                match block
+                    .iter_mut()
+                    .find(|activity| activity.tx_hash == transfer.tx_hash)
+                {
+                    Some(activity) => {
+                        activity.time = activity.time.or(transfer.time);
+                        activity.transfers.push(transfer);
+                    }
+                    None => block.push(Activity {
+                        tx_hash: transfer.tx_hash.clone(),
+                        height,
+                        time: transfer.time,
+                        transfers: vec![transfer],
+                    }),
+                }
+            }
+            block.sort_by(|a, b| b.tx_hash.cmp(&a.tx_hash));
+            for mut activity in block {
+                if self
+                    .cursor
+                    .as_ref()
+                    .is_some_and(|cursor| !activity.is_after(cursor))
+                {
+                    continue;
+                }
+                activity
+                    .transfers
+                    .sort_by_key(|transfer| (transfer.index, transfer.kind));
+                self.ready.push_back(activity);
+            }
+        }
+    }
+}
+
+impl Oklink {
+    // Normal, internal, token_20, token_721 and token_1155 transfers of
+    // `address`, merged newest first and grouped by transaction. Pass the cursor of the last entry
+    // seen to resume after it, e.g. when the stream ended with an error.
+    pub fn activity_feed<'a>(
+        &'a self,
+        address: &'a str,
+        cursor: Option<FeedCursor>,
+    ) -> impl Stream<Item = Result<Activity, TransportError>> + 'a {
+        let state = FeedState {
+            oklink: self,
+            address,
+            cursor,
+            sources: vec![
+                FeedSource::new(TransferKind::Native),
+                FeedSource::new(TransferKind::Internal),
+                FeedSource::token(ProtocolType::Token20),
+                FeedSource::token(ProtocolType::Token721),
+                FeedSource::token(ProtocolType::Token1155),
+            ],
+            ready: VecDeque::new(),
+        };
+        stream::try_unfold(state, |mut state| async move {
+            Ok(state.next().await?.map(|activity| (activity, state)))
+        })
+    }
+
+    async fn feed_page(
+        &self,
+        kind: TransferKind,
+        protocol_type: Option<ProtocolType>,
+        address: &str,
+        page: u32,
+        end_height: Option<&str>,
+    ) -> Result<(Vec<Transfer>, u32), TransportError> {
+        let page = page.to_string();
+        let (transfers, total_page) = match kind {
+            TransferKind::Native => {
+                let response = self
+                    .address_normal_transaction_list(
+                        address,
+                        None,
+                        end_height,
+                        None,
+                        Some(&page),
+                        Some(PAGE_LIMIT),
+                    )
+                    .await?;
+                (response.transfers(), response.data.total_page)
+            }
+            TransferKind::Internal => {
+                let response = self
+                    .address_internal_transaction_list(
+                        address,
+                        None,
+                        end_height,
+                        None,
+                        Some(&page),
+                        Some(PAGE_LIMIT),
+                    )
+                    .await?;
+                (response.transfers(), response.data.total_page)
+            }
+            TransferKind::Token => {
+                let protocol_type = protocol_type.unwrap_or(ProtocolType::Token20);
+                let response = self
+                    .address_token_transaction_list_by_height(
+                        address,
+                        protocol_type.as_str(),
+                        None,
+                        None,
+                        end_height,
+                        Some(&page),
+                        Some(PAGE_LIMIT),
+                    )
+                    .await?;
+                (response.transfers(), response.data.total_page)
+            }
+        };
+        Ok((transfers, total_page.parse().unwrap_or(1)))
+    }
+}
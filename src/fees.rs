@@ -0,0 +1,232 @@
+use std::collections::BTreeMap;
+
+use crate::amount::{AmountError, TokenAmount};
+use crate::kaia::KaiaTransaction;
+use crate::oklink::{Oklink, TransactionDetail};
+use crate::transport::TransportError;
+
+const PAGE_LIMIT: &str = "50";
+const DETAILS_BATCH: usize = 20;
+const NATIVE_DECIMALS: u32 = 18;
+const NATIVE_SYMBOL: &str = "KAIA";
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+#[derive(Debug)]
+pub enum FeeError {
+    Http(TransportError),
+    Amount(AmountError),
+}
+
+impl From<TransportError> for FeeError {
+    fn from(e: TransportError) -> Self {
+        FeeError::Http(e)
+    }
+}
+
+impl From<AmountError> for FeeError {
+    fn from(e: AmountError) -> Self {
+        FeeError::Amount(e)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MonthlyFees {
+    // Formatted as "YYYY-MM" in UTC.
+    pub month: String,
+    pub transaction_count: usize,
+    pub gas_used: u64,
+    pub fees_paid: TokenAmount,
+    pub delegated_transaction_count: usize,
+    pub delegated_fees: TokenAmount,
+    pub sponsored_transaction_count: usize,
+    pub sponsored_fees: TokenAmount,
+    pub unknown_ratio_transaction_count: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct FeeReport {
+    pub address: String,
+    pub transaction_count: usize,
+    pub total_gas_used: u64,
+    // Fees the address paid, as sender or as fee payer. For partially
+    // delegated transactions only its own share of the fee is counted here.
+    pub total_fees_paid: TokenAmount,
+    pub delegated_transaction_count: usize,
+    // Fees paid by fee payers on the address' behalf.
+    pub delegated_fees: TokenAmount,
+    // Transactions of other senders whose fee the address paid, and the part
+    // of `total_fees_paid` that went to them.
+    pub sponsored_transaction_count: usize,
+    pub sponsored_fees: TokenAmount,
+    // WithRatio transactions that don't report their fee ratio. Their fees
+    // are left out of the totals since the split can't be known.
+    pub unknown_ratio_transaction_count: usize,
+    pub months: Vec<MonthlyFees>,
+}
+
+fn zero() -> TokenAmount {
+    TokenAmount::from_raw(0, NATIVE_DECIMALS)
+        .unwrap()
+        .with_symbol(NATIVE_SYMBOL)
+}
+
+fn native(raw: u128) -> Result<TokenAmount, AmountError> {
+    Ok(TokenAmount::from_raw(raw, NATIVE_DECIMALS)?.with_symbol(NATIVE_SYMBOL))
+}
+
+fn add(total: &TokenAmount, fee: &TokenAmount) -> Result<TokenAmount, AmountError> {
+    total
+        .checked_add(fee)
+        .ok_or_else(|| AmountError::Overflow(fee.to_units_string()))
+}
+
+// Converts a millisecond Unix timestamp to "YYYY-MM" using the civil-from-days
+// algorithm, which avoids pulling in a date crate for one conversion.
+pub fn month_of(timestamp_ms: u64) -> String {
+    let days = (timestamp_ms / DAY_MS) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}", year, month)
+}
+
+// Covers transactions the address sent and transactions whose fee it paid as
+// fee payer.
+pub fn fee_report(address: &str, details: &[TransactionDetail]) -> Result<FeeReport, AmountError> {
+    let mut months: BTreeMap<String, MonthlyFees> = BTreeMap::new();
+    let mut report = FeeReport {
+        address: address.to_string(),
+        transaction_count: 0,
+        total_gas_used: 0,
+        total_fees_paid: zero(),
+        delegated_transaction_count: 0,
+        delegated_fees: zero(),
+        sponsored_transaction_count: 0,
+        sponsored_fees: zero(),
+        unknown_ratio_transaction_count: 0,
+        months: Vec::new(),
+    };
+
+    for detail in details {
+        let is_sender = detail.from.eq_ignore_ascii_case(address);
+        let is_fee_payer = detail
+            .fee_payer
+            .as_deref()
+            .is_some_and(|payer| payer.eq_ignore_ascii_case(address));
+        if !is_sender && !is_fee_payer {
+            continue;
+        }
+        let gas_used = detail.gas_used.parse::<u64>().unwrap_or(0);
+        let fee =
+            TokenAmount::from_units(&detail.txfee, NATIVE_DECIMALS)?.with_symbol(NATIVE_SYMBOL);
+        let month = month_of(detail.transaction_time.parse::<u64>().unwrap_or(0));
+        let monthly = months.entry(month.clone()).or_insert_with(|| MonthlyFees {
+            month,
+            transaction_count: 0,
+            gas_used: 0,
+            fees_paid: zero(),
+            delegated_transaction_count: 0,
+            delegated_fees: zero(),
+            sponsored_transaction_count: 0,
+            sponsored_fees: zero(),
+            unknown_ratio_transaction_count: 0,
+        });
+        report.transaction_count += 1;
+        report.total_gas_used += gas_used;
+        monthly.transaction_count += 1;
+        monthly.gas_used += gas_used;
+
+        // Paying both shares makes the split irrelevant.
+        if is_sender && is_fee_payer {
+            report.total_fees_paid = add(&report.total_fees_paid, &fee)?;
+            monthly.fees_paid = add(&monthly.fees_paid, &fee)?;
+            continue;
+        }
+        if detail.fee_ratio_unknown() {
+            report.unknown_ratio_transaction_count += 1;
+            monthly.unknown_ratio_transaction_count += 1;
+            continue;
+        }
+        let ratio = detail.fee_ratio().unwrap_or(0) as u128;
+        let payer_share = native(
+            fee.raw()
+                .checked_mul(ratio)
+                .ok_or_else(|| AmountError::Overflow(fee.to_units_string()))?
+                / 100,
+        )?;
+        if is_sender {
+            let own_fee = fee
+                .checked_sub(&payer_share)
+                .ok_or_else(|| AmountError::Overflow(fee.to_units_string()))?;
+            report.total_fees_paid = add(&report.total_fees_paid, &own_fee)?;
+            monthly.fees_paid = add(&monthly.fees_paid, &own_fee)?;
+            if ratio > 0 {
+                report.delegated_transaction_count += 1;
+                report.delegated_fees = add(&report.delegated_fees, &payer_share)?;
+                monthly.delegated_transaction_count += 1;
+                monthly.delegated_fees = add(&monthly.delegated_fees, &payer_share)?;
+            }
+        } else {
+            report.total_fees_paid = add(&report.total_fees_paid, &payer_share)?;
+            report.sponsored_transaction_count += 1;
+            report.sponsored_fees = add(&report.sponsored_fees, &payer_share)?;
+            monthly.fees_paid = add(&monthly.fees_paid, &payer_share)?;
+            monthly.sponsored_transaction_count += 1;
+            monthly.sponsored_fees = add(&monthly.sponsored_fees, &payer_share)?;
+        }
+    }
+
+    report.months = months.into_values().collect();
+    Ok(report)
+}
+
+impl Oklink {
+    // Reads every transaction the explorer lists for the address, sent or
+    // received, so that transactions it only paid the fee for are included
+    // when the explorer lists them for the fee payer.
+    pub async fn fee_report(
+        &self,
+        address: &str,
+        start_block_height: Option<&str>,
+        end_block_height: Option<&str>,
+    ) -> Result<FeeReport, FeeError> {
+        let mut tx_ids: Vec<String> = Vec::new();
+        let mut page = 1u32;
+        loop {
+            let page_str = page.to_string();
+            let response = self
+                .address_normal_transaction_list(
+                    address,
+                    start_block_height,
+                    end_block_height,
+                    None,
+                    Some(&page_str),
+                    Some(PAGE_LIMIT),
+                )
+                .await?;
+            tx_ids.extend(
+                response
+                    .data
+                    .transaction_list
+                    .into_iter()
+                    .map(|tx| tx.tx_id),
+            );
+            if page >= response.data.total_page.parse::<u32>().unwrap_or(1) {
+                break;
+            }
+            page += 1;
+        }
+
+        let mut details = Vec::with_capacity(tx_ids.len());
+        for chunk in tx_ids.chunks(DETAILS_BATCH) {
+            let chunk: Vec<&str> = chunk.iter().map(String::as_str).collect();
+            details.extend(self.batch_transaction_details(&chunk).await?.data);
+        }
+        Ok(fee_report(address, &details)?)
+    }
+}
@@ -0,0 +1,378 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Write;
+
+use crate::labels::{matches_keywords, EXCHANGE_KEYWORDS};
+use crate::oklink::Oklink;
+use crate::transport::TransportError;
+
+const PAGE_LIMIT: &str = "50";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowDirection {
+    Outgoing,
+    Incoming,
+    Both,
+}
+
+#[derive(Debug, Clone)]
+pub struct TraceOptions {
+    pub max_depth: usize,
+    // Transfers below this amount, in token units, are ignored unless the
+    // asset has its own entry in `min_amounts`.
+    pub min_amount: f64,
+    // Per-asset thresholds keyed by token contract address; "" is native KAIA.
+    pub min_amounts: HashMap<String, f64>,
+    // Hard cap on the number of addresses expanded.
+    pub max_addresses: usize,
+    // Pages of history fetched per address and transaction list; `None`
+    // reads every page.
+    pub max_pages: Option<u32>,
+    pub direction: FlowDirection,
+    pub include_native: bool,
+    // e.g. "token_20"; `None` skips token transfers.
+    pub protocol_type: Option<String>,
+    // Addresses whose entity label contains one of these are not expanded.
+    pub exchange_keywords: Vec<String>,
+}
+
+impl Default for TraceOptions {
+    fn default() -> Self {
+        TraceOptions {
+            max_depth: 3,
+            min_amount: 0.0,
+            min_amounts: HashMap::new(),
+            max_addresses: 200,
+            max_pages: None,
+            direction: FlowDirection::Outgoing,
+            include_native: true,
+            protocol_type: Some("token_20".to_string()),
+            exchange_keywords: EXCHANGE_KEYWORDS.iter().map(|k| k.to_string()).collect(),
+        }
+    }
+}
+
+impl TraceOptions {
+    pub fn with_min_amount(mut self, token_contract_address: &str, amount: f64) -> Self {
+        self.min_amounts
+            .insert(token_contract_address.to_lowercase(), amount);
+        self
+    }
+
+    pub fn min_amount_for(&self, token_contract_address: &str) -> f64 {
+        self.min_amounts
+            .iter()
+            .find(|(token, _)| token.eq_ignore_ascii_case(token_contract_address))
+            .map_or(self.min_amount, |(_, amount)| *amount)
+    }
+
+    fn last_page(&self, page: u32) -> bool {
+        self.max_pages.is_some_and(|max| page >= max)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphNode {
+    pub address: String,
+    pub depth: usize,
+    pub label: Option<String>,
+    pub is_exchange: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    pub tx_id: String,
+    pub amount: f64,
+    pub symbol: String,
+    // Empty for native KAIA transfers.
+    pub token_contract_address: String,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TransferGraph {
+    pub seed: String,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+fn dot_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+impl TransferGraph {
+    pub fn node(&self, address: &str) -> Option<&GraphNode> {
+        self.nodes
+            .iter()
+            .find(|n| n.address.eq_ignore_ascii_case(address))
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph transfers {\n");
+        for node in &self.nodes {
+            let label = match &node.label {
+                Some(label) => format!("{}\\n{}", dot_escape(label), dot_escape(&node.address)),
+                None => dot_escape(&node.address),
+            };
+            let shape = if node.is_exchange { "box" } else { "ellipse" };
+            let _ = writeln!(
+                out,
+                "  \"{}\" [label=\"{}\", shape={}];",
+                dot_escape(&node.address),
+                label,
+                shape
+            );
+        }
+        for edge in &self.edges {
+            let _ = writeln!(
+                out,
+                "  \"{}\" -> \"{}\" [label=\"{} {}\", tooltip=\"tx {} at {}\"];",
+                dot_escape(&edge.from),
+                dot_escape(&edge.to),
+                edge.amount,
+                dot_escape(&edge.symbol),
+                dot_escape(&edge.tx_id),
+                edge.timestamp
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    pub fn to_graphml(&self) -> String {
+        let mut out = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n\
+             \x20 <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n\
+             \x20 <key id=\"depth\" for=\"node\" attr.name=\"depth\" attr.type=\"int\"/>\n\
+             \x20 <key id=\"exchange\" for=\"node\" attr.name=\"exchange\" attr.type=\"boolean\"/>\n\
+             \x20 <key id=\"tx\" for=\"edge\" attr.name=\"tx\" attr.type=\"string\"/>\n\
+             \x20 <key id=\"amount\" for=\"edge\" attr.name=\"amount\" attr.type=\"double\"/>\n\
+             \x20 <key id=\"symbol\" for=\"edge\" attr.name=\"symbol\" attr.type=\"string\"/>\n\
+             \x20 <key id=\"token\" for=\"edge\" attr.name=\"token\" attr.type=\"string\"/>\n\
+             \x20 <key id=\"time\" for=\"edge\" attr.name=\"time\" attr.type=\"long\"/>\n\
+             \x20 <graph id=\"transfers\" edgedefault=\"directed\">\n",
+        );
+        for node in &self.nodes {
+            let _ = writeln!(out, "    <node id=\"{}\">", xml_escape(&node.address));
+            if let Some(label) = &node.label {
+                let _ = writeln!(
+                    out,
+                    "      <data key=\"label\">{}</data>",
+                    xml_escape(label)
+                );
+            }
+            let _ = writeln!(out, "      <data key=\"depth\">{}</data>", node.depth);
+            let _ = writeln!(
+                out,
+                "      <data key=\"exchange\">{}</data>",
+                node.is_exchange
+            );
+            out.push_str("    </node>\n");
+        }
+        for edge in &self.edges {
+            let _ = writeln!(
+                out,
+                "    <edge source=\"{}\" target=\"{}\">",
+                xml_escape(&edge.from),
+                xml_escape(&edge.to)
+            );
+            let _ = writeln!(
+                out,
+                "      <data key=\"tx\">{}</data>",
+                xml_escape(&edge.tx_id)
+            );
+            let _ = writeln!(out, "      <data key=\"amount\">{}</data>", edge.amount);
+            let _ = writeln!(
+                out,
+                "      <data key=\"symbol\">{}</data>",
+                xml_escape(&edge.symbol)
+            );
+            let _ = writeln!(
+                out,
+                "      <data key=\"token\">{}</data>",
+                xml_escape(&edge.token_contract_address)
+            );
+            let _ = writeln!(out, "      <data key=\"time\">{}</data>", edge.timestamp);
+            out.push_str("    </edge>\n");
+        }
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+}
+
+fn follows(direction: FlowDirection, address: &str, from: &str, to: &str) -> bool {
+    match direction {
+        FlowDirection::Outgoing => from.eq_ignore_ascii_case(address),
+        FlowDirection::Incoming => to.eq_ignore_ascii_case(address),
+        FlowDirection::Both => {
+            from.eq_ignore_ascii_case(address) || to.eq_ignore_ascii_case(address)
+        }
+    }
+}
+
+impl Oklink {
+    // Breadth-first expansion of counterparties starting at `seed`. Every
+    // address at a level is labelled in one batch before it is expanded, and
+    // labelled exchange addresses are kept as leaves.
+    pub async fn trace_funds(
+        &self,
+        seed: &str,
+        options: &TraceOptions,
+    ) -> Result<TransferGraph, TransportError> {
+        let mut graph = TransferGraph {
+            seed: seed.to_string(),
+            ..TransferGraph::default()
+        };
+        let mut seen_nodes: HashSet<String> = HashSet::new();
+        let mut seen_edges: HashSet<(String, String, String, String)> = HashSet::new();
+        let mut queue: VecDeque<(String, usize)> = VecDeque::new();
+        seen_nodes.insert(seed.to_lowercase());
+        queue.push_back((seed.to_string(), 0));
+        let mut expanded = 0usize;
+
+        while !queue.is_empty() {
+            let level: Vec<(String, usize)> = queue.drain(..).collect();
+            let addresses: Vec<&str> = level.iter().map(|(a, _)| a.as_str()).collect();
+            let labels: HashMap<String, String> = self.entity_labels(&addresses).await?;
+
+            for (address, depth) in level {
+                let label = labels.get(&address.to_lowercase()).cloned();
+                let is_exchange = label
+                    .as_ref()
+                    .is_some_and(|label| matches_keywords(label, &options.exchange_keywords));
+                graph.nodes.push(GraphNode {
+                    address: address.clone(),
+                    depth,
+                    label,
+                    is_exchange,
+                });
+                if is_exchange || depth >= options.max_depth || expanded >= options.max_addresses {
+                    continue;
+                }
+                expanded += 1;
+
+                for edge in self.address_transfers(&address, options).await? {
+                    let counterparty = if edge.from.eq_ignore_ascii_case(&address) {
+                        edge.to.clone()
+                    } else {
+                        edge.from.clone()
+                    };
+                    let key = (
+                        edge.tx_id.clone(),
+                        edge.from.to_lowercase(),
+                        edge.to.to_lowercase(),
+                        edge.token_contract_address.to_lowercase(),
+                    );
+                    if !seen_edges.insert(key) {
+                        continue;
+                    }
+                    graph.edges.push(edge);
+                    if !counterparty.is_empty() && seen_nodes.insert(counterparty.to_lowercase()) {
+                        queue.push_back((counterparty, depth + 1));
+                    }
+                }
+            }
+        }
+        Ok(graph)
+    }
+
+    async fn address_transfers(
+        &self,
+        address: &str,
+        options: &TraceOptions,
+    ) -> Result<Vec<GraphEdge>, TransportError> {
+        let mut edges = Vec::new();
+        if options.include_native {
+            let is_from_or_to = match options.direction {
+                FlowDirection::Outgoing => Some("from"),
+                FlowDirection::Incoming => Some("to"),
+                FlowDirection::Both => None,
+            };
+            let mut page = 1u32;
+            loop {
+                let page_str = page.to_string();
+                let response = self
+                    .address_normal_transaction_list(
+                        address,
+                        None,
+                        None,
+                        is_from_or_to,
+                        Some(&page_str),
+                        Some(PAGE_LIMIT),
+                    )
+                    .await?;
+                edges.extend(
+                    response
+                        .data
+                        .transaction_list
+                        .into_iter()
+                        .map(|tx| GraphEdge {
+                            amount: tx.amount.parse().unwrap_or(0.0),
+                            timestamp: tx.transaction_time.parse().unwrap_or(0),
+                            from: tx.from,
+                            to: tx.to,
+                            tx_id: tx.tx_id,
+                            symbol: tx.symbol,
+                            token_contract_address: String::new(),
+                        }),
+                );
+                let total_page = response.data.total_page.parse::<u32>().unwrap_or(1);
+                if page >= total_page || options.last_page(page) {
+                    break;
+                }
+                page += 1;
+            }
+        }
+        if let Some(protocol_type) = &options.protocol_type {
+            let mut page = 1u32;
+            loop {
+                let page_str = page.to_string();
+                let response = self
+                    .address_token_transaction_list(
+                        address,
+                        protocol_type,
+                        None,
+                        Some(&page_str),
+                        Some(PAGE_LIMIT),
+                    )
+                    .await?;
+                edges.extend(
+                    response
+                        .data
+                        .transaction_lists
+                        .into_iter()
+                        .map(|tx| GraphEdge {
+                            amount: tx.amount.parse().unwrap_or(0.0),
+                            timestamp: tx.transaction_time.parse().unwrap_or(0),
+                            from: tx.from,
+                            to: tx.to,
+                            tx_id: tx.tx_id,
+                            symbol: tx.symbol,
+                            token_contract_address: tx.token_contract_address,
+                        }),
+                );
+                let total_page = response.data.total_page.parse::<u32>().unwrap_or(1);
+                if page >= total_page || options.last_page(page) {
+                    break;
+                }
+                page += 1;
+            }
+        }
+        edges.retain(|edge| {
+            edge.amount >= options.min_amount_for(&edge.token_contract_address)
+                && follows(options.direction, address, &edge.from, &edge.to)
+        });
+        Ok(edges)
+    }
+}
@@ -0,0 +1,569 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::oklink::{AddressData, BlockTransaction, NormalTransaction, Oklink, TransactionDetail};
+use crate::transport::{HttpMethod, HttpRequest, TransportError};
+
+pub const DEFAULT_RPC_URL: &str = "https://public-en.node.kaia.io";
+
+// Kaia's native transaction types. Every base type has a fee-delegated
+// variant (the fee payer covers the whole fee) and a partial variant where
+// the fee payer covers `fee_ratio` percent of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KaiaTxType {
+    Legacy,
+    ValueTransfer,
+    FeeDelegatedValueTransfer,
+    FeeDelegatedValueTransferWithRatio,
+    ValueTransferMemo,
+    FeeDelegatedValueTransferMemo,
+    FeeDelegatedValueTransferMemoWithRatio,
+    AccountUpdate,
+    FeeDelegatedAccountUpdate,
+    FeeDelegatedAccountUpdateWithRatio,
+    SmartContractDeploy,
+    FeeDelegatedSmartContractDeploy,
+    FeeDelegatedSmartContractDeployWithRatio,
+    SmartContractExecution,
+    FeeDelegatedSmartContractExecution,
+    FeeDelegatedSmartContractExecutionWithRatio,
+    Cancel,
+    FeeDelegatedCancel,
+    FeeDelegatedCancelWithRatio,
+    ChainDataAnchoring,
+    FeeDelegatedChainDataAnchoring,
+    FeeDelegatedChainDataAnchoringWithRatio,
+    EthereumAccessList,
+    EthereumDynamicFee,
+}
+
+const TX_TYPES: [(KaiaTxType, u16, &str); 24] = [
+    (KaiaTxType::Legacy, 0x00, "TxTypeLegacyTransaction"),
+    (KaiaTxType::ValueTransfer, 0x08, "TxTypeValueTransfer"),
+    (
+        KaiaTxType::FeeDelegatedValueTransfer,
+        0x09,
+        "TxTypeFeeDelegatedValueTransfer",
+    ),
+    (
+        KaiaTxType::FeeDelegatedValueTransferWithRatio,
+        0x0a,
+        "TxTypeFeeDelegatedValueTransferWithRatio",
+    ),
+    (
+        KaiaTxType::ValueTransferMemo,
+        0x10,
+        "TxTypeValueTransferMemo",
+    ),
+    (
+        KaiaTxType::FeeDelegatedValueTransferMemo,
+        0x11,
+        "TxTypeFeeDelegatedValueTransferMemo",
+    ),
+    (
+        KaiaTxType::FeeDelegatedValueTransferMemoWithRatio,
+        0x12,
+        "TxTypeFeeDelegatedValueTransferMemoWithRatio",
+    ),
+    (KaiaTxType::AccountUpdate, 0x20, "TxTypeAccountUpdate"),
+    (
+        KaiaTxType::FeeDelegatedAccountUpdate,
+        0x21,
+        "TxTypeFeeDelegatedAccountUpdate",
+    ),
+    (
+        KaiaTxType::FeeDelegatedAccountUpdateWithRatio,
+        0x22,
+        "TxTypeFeeDelegatedAccountUpdateWithRatio",
+    ),
+    (
+        KaiaTxType::SmartContractDeploy,
+        0x28,
+        "TxTypeSmartContractDeploy",
+    ),
+    (
+        KaiaTxType::FeeDelegatedSmartContractDeploy,
+        0x29,
+        "TxTypeFeeDelegatedSmartContractDeploy",
+    ),
+    (
+        KaiaTxType::FeeDelegatedSmartContractDeployWithRatio,
+        0x2a,
+        "TxTypeFeeDelegatedSmartContractDeployWithRatio",
+    ),
+    (
+        KaiaTxType::SmartContractExecution,
+        0x30,
+        "TxTypeSmartContractExecution",
+    ),
+    (
+        KaiaTxType::FeeDelegatedSmartContractExecution,
+        0x31,
+        "TxTypeFeeDelegatedSmartContractExecution",
+    ),
+    (
+        KaiaTxType::FeeDelegatedSmartContractExecutionWithRatio,
+        0x32,
+        "TxTypeFeeDelegatedSmartContractExecutionWithRatio",
+    ),
+    (KaiaTxType::Cancel, 0x38, "TxTypeCancel"),
+    (
+        KaiaTxType::FeeDelegatedCancel,
+        0x39,
+        "TxTypeFeeDelegatedCancel",
+    ),
+    (
+        KaiaTxType::FeeDelegatedCancelWithRatio,
+        0x3a,
+        "TxTypeFeeDelegatedCancelWithRatio",
+    ),
+    (
+        KaiaTxType::ChainDataAnchoring,
+        0x48,
+        "TxTypeChainDataAnchoring",
+    ),
+    (
+        KaiaTxType::FeeDelegatedChainDataAnchoring,
+        0x49,
+        "TxTypeFeeDelegatedChainDataAnchoring",
+    ),
+    (
+        KaiaTxType::FeeDelegatedChainDataAnchoringWithRatio,
+        0x4a,
+        "TxTypeFeeDelegatedChainDataAnchoringWithRatio",
+    ),
+    (
+        KaiaTxType::EthereumAccessList,
+        0x7801,
+        "TxTypeEthereumAccessList",
+    ),
+    (
+        KaiaTxType::EthereumDynamicFee,
+        0x7802,
+        "TxTypeEthereumDynamicFee",
+    ),
+];
+
+impl KaiaTxType {
+    pub fn from_code(code: u16) -> Option<Self> {
+        TX_TYPES
+            .iter()
+            .find(|(_, c, _)| *c == code)
+            .map(|(tx_type, _, _)| *tx_type)
+    }
+
+    // Accepts hex ("0x09"), decimal ("9") or the canonical name
+    // ("TxTypeFeeDelegatedValueTransfer", with or without the prefix). The
+    // EIP-2718 type numbers 1 and 2, as reported for Ethereum transactions,
+    // map to their 0x78-prefixed Kaia codes.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let code = match value
+            .strip_prefix("0x")
+            .or_else(|| value.strip_prefix("0X"))
+        {
+            Some(hex) => u16::from_str_radix(hex, 16).ok(),
+            None => value.parse::<u16>().ok(),
+        };
+        if let Some(code) = code {
+            return match code {
+                1 => Some(KaiaTxType::EthereumAccessList),
+                2 => Some(KaiaTxType::EthereumDynamicFee),
+                code => KaiaTxType::from_code(code),
+            };
+        }
+        let name = value.strip_prefix("TxType").unwrap_or(value);
+        TX_TYPES
+            .iter()
+            .find(|(_, _, n)| n["TxType".len()..].eq_ignore_ascii_case(name))
+            .map(|(tx_type, _, _)| *tx_type)
+    }
+
+    pub fn code(&self) -> u16 {
+        TX_TYPES.iter().find(|(t, _, _)| t == self).unwrap().1
+    }
+
+    pub fn name(&self) -> &'static str {
+        TX_TYPES.iter().find(|(t, _, _)| t == self).unwrap().2
+    }
+
+    // Kaia-native types sit below 0x7800; Ethereum-compatible envelopes use
+    // the 0x78 prefix and legacy is shared by both.
+    pub fn is_kaia_native(&self) -> bool {
+        !matches!(
+            self,
+            KaiaTxType::Legacy | KaiaTxType::EthereumAccessList | KaiaTxType::EthereumDynamicFee
+        )
+    }
+
+    pub fn is_fee_delegated(&self) -> bool {
+        self.is_kaia_native() && self.code() & 0x07 != 0
+    }
+
+    pub fn has_fee_ratio(&self) -> bool {
+        self.is_kaia_native() && self.code() & 0x07 == 0x02
+    }
+
+    // The type with fee delegation stripped, e.g. FeeDelegatedValueTransferWithRatio -> ValueTransfer.
+    pub fn base(&self) -> KaiaTxType {
+        if self.is_kaia_native() {
+            KaiaTxType::from_code(self.code() & !0x07).unwrap_or(*self)
+        } else {
+            *self
+        }
+    }
+}
+
+pub trait KaiaTransaction {
+    fn transaction_type(&self) -> &str;
+    fn sender(&self) -> &str;
+    fn fee_payer(&self) -> Option<&str>;
+    fn raw_fee_ratio(&self) -> Option<&str>;
+
+    fn tx_type(&self) -> Option<KaiaTxType> {
+        KaiaTxType::parse(self.transaction_type())
+    }
+
+    fn is_fee_delegated(&self) -> bool {
+        self.tx_type().is_some_and(|t| t.is_fee_delegated())
+            || self.fee_payer().is_some_and(|payer| {
+                !payer.is_empty() && !payer.eq_ignore_ascii_case(self.sender())
+            })
+    }
+
+    // Percentage of the fee covered by the fee payer: 100 for full delegation,
+    // 1-99 for partial delegation and None when the sender pays everything or
+    // the share is unknown (see `fee_ratio_unknown`).
+    fn fee_ratio(&self) -> Option<u8> {
+        if !self.is_fee_delegated() || self.fee_ratio_unknown() {
+            return None;
+        }
+        match self.valid_fee_ratio() {
+            Some(ratio) => Some(ratio),
+            None => Some(100),
+        }
+    }
+
+    // A partially delegated (WithRatio) transaction that doesn't report a
+    // valid ratio, so the split between sender and fee payer is unknown.
+    fn fee_ratio_unknown(&self) -> bool {
+        self.tx_type().is_some_and(|t| t.has_fee_ratio()) && self.valid_fee_ratio().is_none()
+    }
+
+    fn valid_fee_ratio(&self) -> Option<u8> {
+        self.raw_fee_ratio()
+            .and_then(|ratio| ratio.trim().parse::<u8>().ok())
+            .filter(|ratio| (1..=99).contains(ratio))
+    }
+}
+
+impl KaiaTransaction for TransactionDetail {
+    fn transaction_type(&self) -> &str {
+        &self.transaction_type
+    }
+
+    fn sender(&self) -> &str {
+        &self.from
+    }
+
+    fn fee_payer(&self) -> Option<&str> {
+        self.fee_payer.as_deref()
+    }
+
+    fn raw_fee_ratio(&self) -> Option<&str> {
+        self.fee_ratio.as_deref()
+    }
+}
+
+impl KaiaTransaction for BlockTransaction {
+    fn transaction_type(&self) -> &str {
+        &self.transaction_type
+    }
+
+    fn sender(&self) -> &str {
+        &self.from
+    }
+
+    fn fee_payer(&self) -> Option<&str> {
+        self.fee_payer.as_deref()
+    }
+
+    fn raw_fee_ratio(&self) -> Option<&str> {
+        self.fee_ratio.as_deref()
+    }
+}
+
+impl KaiaTransaction for NormalTransaction {
+    fn transaction_type(&self) -> &str {
+        &self.transaction_type
+    }
+
+    fn sender(&self) -> &str {
+        &self.from
+    }
+
+    fn fee_payer(&self) -> Option<&str> {
+        None
+    }
+
+    fn raw_fee_ratio(&self) -> Option<&str> {
+        None
+    }
+}
+
+pub fn filter_by_type<'a, T: KaiaTransaction>(
+    transactions: &'a [T],
+    types: &'a [KaiaTxType],
+) -> impl Iterator<Item = &'a T> + 'a {
+    transactions
+        .iter()
+        .filter(move |tx| tx.tx_type().is_some_and(|t| types.contains(&t)))
+}
+
+pub fn filter_by_base_type<'a, T: KaiaTransaction>(
+    transactions: &'a [T],
+    base: KaiaTxType,
+) -> impl Iterator<Item = &'a T> + 'a {
+    transactions
+        .iter()
+        .filter(move |tx| tx.tx_type().is_some_and(|t| t.base() == base.base()))
+}
+
+pub fn fee_delegated<T: KaiaTransaction>(transactions: &[T]) -> impl Iterator<Item = &T> {
+    transactions.iter().filter(|tx| tx.is_fee_delegated())
+}
+
+#[derive(Debug)]
+pub enum AccountError {
+    Http(TransportError),
+    Rpc(String),
+    InvalidKey(String),
+}
+
+impl From<TransportError> for AccountError {
+    fn from(e: TransportError) -> Self {
+        AccountError::Http(e)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicKey {
+    pub x: String,
+    pub y: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeightedPublicKey {
+    pub weight: u32,
+    pub key: PublicKey,
+}
+
+// Kaia decouples keys from addresses; an account's key decides who may sign
+// for it. Key type codes follow `kaia_getAccountKey`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "Value")]
+pub enum AccountKey {
+    // 0x01: the address is derived from the signing key, as on Ethereum.
+    Legacy,
+    // 0x02: a single key that is not derived from the address.
+    Public(PublicKey),
+    // 0x03: every signature fails; the account can no longer send transactions.
+    Fail,
+    // 0x04: signatures must reach `threshold` total weight.
+    WeightedMultiSig {
+        threshold: u32,
+        keys: Vec<WeightedPublicKey>,
+    },
+    // 0x05: separate keys for transactions, account updates and fee payment.
+    RoleBased {
+        transaction: Box<AccountKey>,
+        account_update: Option<Box<AccountKey>>,
+        fee_payer: Option<Box<AccountKey>>,
+    },
+}
+
+fn public_key(value: &Value) -> Result<PublicKey, AccountError> {
+    let field = |name: &str| {
+        value[name]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| AccountError::InvalidKey(format!("missing public key {}", name)))
+    };
+    Ok(PublicKey {
+        x: field("x")?,
+        y: field("y")?,
+    })
+}
+
+impl AccountKey {
+    pub fn key_type(&self) -> u8 {
+        match self {
+            AccountKey::Legacy => 0x01,
+            AccountKey::Public(_) => 0x02,
+            AccountKey::Fail => 0x03,
+            AccountKey::WeightedMultiSig { .. } => 0x04,
+            AccountKey::RoleBased { .. } => 0x05,
+        }
+    }
+
+    pub fn is_multisig(&self) -> bool {
+        match self {
+            AccountKey::WeightedMultiSig { .. } => true,
+            AccountKey::RoleBased { transaction, .. } => transaction.is_multisig(),
+            _ => false,
+        }
+    }
+
+    // Legacy keys mean the address' own private key controls the account.
+    pub fn is_decoupled(&self) -> bool {
+        !matches!(self, AccountKey::Legacy)
+    }
+
+    pub fn from_json(value: &Value) -> Result<AccountKey, AccountError> {
+        let key_type = value["keyType"]
+            .as_u64()
+            .ok_or_else(|| AccountError::InvalidKey("missing keyType".to_string()))?;
+        let key = &value["key"];
+        match key_type {
+            1 => Ok(AccountKey::Legacy),
+            2 => Ok(AccountKey::Public(public_key(key)?)),
+            3 => Ok(AccountKey::Fail),
+            4 => {
+                let threshold = key["threshold"]
+                    .as_u64()
+                    .ok_or_else(|| AccountError::InvalidKey("missing threshold".to_string()))?;
+                let keys = key["keys"]
+                    .as_array()
+                    .ok_or_else(|| AccountError::InvalidKey("missing keys".to_string()))?
+                    .iter()
+                    .map(|weighted| {
+                        Ok(WeightedPublicKey {
+                            weight: weighted["weight"].as_u64().unwrap_or(0) as u32,
+                            key: public_key(&weighted["key"])?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, AccountError>>()?;
+                Ok(AccountKey::WeightedMultiSig {
+                    threshold: threshold as u32,
+                    keys,
+                })
+            }
+            5 => {
+                let roles = key
+                    .as_array()
+                    .ok_or_else(|| AccountError::InvalidKey("missing roles".to_string()))?;
+                // Unset roles are reported as nil keys (key type 0) and fall
+                // back to the transaction key.
+                let role = |index: usize| -> Result<Option<Box<AccountKey>>, AccountError> {
+                    match roles.get(index) {
+                        Some(role) if role["keyType"].as_u64().unwrap_or(0) != 0 => {
+                            Ok(Some(Box::new(AccountKey::from_json(role)?)))
+                        }
+                        _ => Ok(None),
+                    }
+                };
+                Ok(AccountKey::RoleBased {
+                    transaction: role(0)?.ok_or_else(|| {
+                        AccountError::InvalidKey("missing transaction role".to_string())
+                    })?,
+                    account_update: role(1)?,
+                    fee_payer: role(2)?,
+                })
+            }
+            other => Err(AccountError::InvalidKey(format!(
+                "unknown keyType {}",
+                other
+            ))),
+        }
+    }
+}
+
+impl TryFrom<Value> for AccountKey {
+    type Error = String;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        AccountKey::from_json(&value).map_err(|e| format!("{:?}", e))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AaAccountInfo {
+    pub is_aa_address: bool,
+    // Factory or deployer of the smart account, when OKLink knows it.
+    pub factory: Option<String>,
+    pub deployment_transaction_hash: Option<String>,
+    pub first_transaction_time: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct KaiaAccount {
+    pub summary: AddressData,
+    pub account_key: Option<AccountKey>,
+    pub aa: Option<AaAccountInfo>,
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    Some(value.to_string()).filter(|v| !v.is_empty())
+}
+
+impl Oklink {
+    // Account keys are not indexed by OKLink, so they are read from a Kaia
+    // node over JSON-RPC.
+    pub async fn account_key(
+        &self,
+        address: &str,
+        rpc_url: &str,
+    ) -> Result<Option<AccountKey>, AccountError> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "kaia_getAccountKey",
+            "params": [address, "latest"],
+        });
+        let request = HttpRequest {
+            method: HttpMethod::Post,
+            url: rpc_url.to_string(),
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            api_key: None,
+            query: Vec::new(),
+            body: Some(request.to_string().into_bytes()),
+        };
+        let response = self.send(request).await?;
+        let response: Value =
+            serde_json::from_slice(&response.body).map_err(TransportError::from)?;
+        if let Some(error) = response.get("error") {
+            return Err(AccountError::Rpc(error.to_string()));
+        }
+        match &response["result"] {
+            Value::Null => Ok(None),
+            result => AccountKey::from_json(result).map(Some),
+        }
+    }
+
+    pub async fn account(&self, address: &str, rpc_url: &str) -> Result<KaiaAccount, AccountError> {
+        let (info, account_key) = futures::try_join!(
+            async {
+                self.evm_address_info(address)
+                    .await
+                    .map_err(AccountError::from)
+            },
+            self.account_key(address, rpc_url),
+        )?;
+        let summary = info.data;
+        let aa = if summary.is_aa_address {
+            Some(AaAccountInfo {
+                is_aa_address: true,
+                factory: non_empty(&summary.create_contract_address),
+                deployment_transaction_hash: non_empty(&summary.create_contract_transaction_hash),
+                first_transaction_time: non_empty(&summary.first_transaction_time),
+            })
+        } else {
+            None
+        };
+        Ok(KaiaAccount {
+            summary,
+            account_key,
+            aa,
+        })
+    }
+}
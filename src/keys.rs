@@ -0,0 +1,449 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::clock::Instant;
+use crate::secret::{ApiKey, CredentialProvider, SecretError};
+
+// OKLink error codes for a rejected key, a request rate limit and an
+// exhausted quota.
+const AUTH_ERROR_CODES: &[&str] = &["50111", "50112", "50113"];
+const RATE_LIMIT_ERROR_CODES: &[&str] = &["50011"];
+const QUOTA_ERROR_CODES: &[&str] = &["50061"];
+
+const DEFAULT_COOLDOWN: Duration = Duration::from_secs(5 * 60);
+// A rate limit clears within seconds, so a key that hit one, possibly the
+// client's only key, is back well inside the client's rate limit wait.
+const DEFAULT_RATE_LIMIT_COOLDOWN: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeySelection {
+    RoundRobin,
+    // The key with the fewest requests in flight, then the fewest requests
+    // in its current rate limit window.
+    LeastLoaded,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub per: Duration,
+}
+
+impl RateLimit {
+    pub fn per_second(requests: u32) -> Self {
+        RateLimit {
+            requests,
+            per: Duration::from_secs(1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyOutcome {
+    Success,
+    AuthError,
+    // Too many requests; the key is rested for the short rate limit cooldown.
+    RateLimited,
+    QuotaError,
+    // Transport failures and other API errors; these never disable a key.
+    Error,
+}
+
+impl KeyOutcome {
+    pub fn classify(status: u16, code: Option<&str>) -> Self {
+        match code {
+            Some(code) if AUTH_ERROR_CODES.contains(&code) => KeyOutcome::AuthError,
+            Some(code) if RATE_LIMIT_ERROR_CODES.contains(&code) => KeyOutcome::RateLimited,
+            Some(code) if QUOTA_ERROR_CODES.contains(&code) => KeyOutcome::QuotaError,
+            _ if status == 401 || status == 403 => KeyOutcome::AuthError,
+            _ if status == 429 => KeyOutcome::RateLimited,
+            Some("0") if status < 400 => KeyOutcome::Success,
+            None if status < 400 => KeyOutcome::Success,
+            _ => KeyOutcome::Error,
+        }
+    }
+}
+
+// Per-key counters. Keys are identified by name so the secret never shows up
+// in metrics.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyStats {
+    pub name: String,
+    pub requests: u64,
+    pub errors: u64,
+    pub auth_errors: u64,
+    pub rate_limit_errors: u64,
+    pub quota_errors: u64,
+    pub times_disabled: u64,
+    pub in_flight: u32,
+    pub disabled: bool,
+}
+
+enum KeySource {
+    Static,
+    Provider(CredentialProvider),
+}
+
+struct PooledKey {
+    name: String,
+    source: KeySource,
+    rate_limit: Option<RateLimit>,
+}
+
+#[derive(Default)]
+struct KeyState {
+    // `None` until a provider-backed key is first loaded, and after an auth
+    // error so the provider is asked for a fresh one.
+    key: Option<ApiKey>,
+    fetched_at: Option<Instant>,
+    // Start times of requests inside the current rate limit window.
+    window: VecDeque<Instant>,
+    disabled_until: Option<Instant>,
+    stats: KeyStats,
+}
+
+impl KeyState {
+    fn refresh(&mut self, now: Instant, rate_limit: Option<RateLimit>) {
+        if self.disabled_until.is_some_and(|until| until <= now) {
+            self.disabled_until = None;
+        }
+        if let Some(limit) = rate_limit {
+            while self
+                .window
+                .front()
+                .is_some_and(|start| now.saturating_duration_since(*start) >= limit.per)
+            {
+                self.window.pop_front();
+            }
+        }
+    }
+
+    // Time until this key can be used again; zero when it is available now.
+    fn wait(&self, now: Instant, rate_limit: Option<RateLimit>) -> Duration {
+        let disabled = self
+            .disabled_until
+            .map_or(Duration::ZERO, |until| until.saturating_duration_since(now));
+        let limited = match (rate_limit, self.window.front()) {
+            (Some(limit), Some(start)) if self.window.len() >= limit.requests as usize => {
+                (*start + limit.per).saturating_duration_since(now)
+            }
+            _ => Duration::ZERO,
+        };
+        disabled.max(limited)
+    }
+}
+
+struct PoolState {
+    next: usize,
+    keys: Vec<KeyState>,
+}
+
+// A key handed out for one request; return it with `KeyPool::release`.
+#[derive(Clone)]
+pub struct KeyLease {
+    index: usize,
+    pub(crate) name: String,
+    pub(crate) key: ApiKey,
+}
+
+pub struct KeyPool {
+    selection: KeySelection,
+    cooldown: Duration,
+    rate_limit_cooldown: Duration,
+    refresh_interval: Option<Duration>,
+    keys: Vec<PooledKey>,
+    state: Mutex<PoolState>,
+}
+
+impl KeyPool {
+    pub fn new(selection: KeySelection) -> Self {
+        KeyPool {
+            selection,
+            cooldown: DEFAULT_COOLDOWN,
+            rate_limit_cooldown: DEFAULT_RATE_LIMIT_COOLDOWN,
+            refresh_interval: None,
+            keys: Vec::new(),
+            state: Mutex::new(PoolState {
+                next: 0,
+                keys: Vec::new(),
+            }),
+        }
+    }
+
+    pub fn key(self, name: &str, key: impl Into<ApiKey>, rate_limit: Option<RateLimit>) -> Self {
+        self.push(name, KeySource::Static, Some(key.into()), rate_limit)
+    }
+
+    // A key loaded from `provider` on first use, after an auth error and,
+    // with `with_refresh_interval`, periodically.
+    pub fn provider(
+        self,
+        name: &str,
+        provider: impl Fn() -> Result<ApiKey, SecretError> + Send + Sync + 'static,
+        rate_limit: Option<RateLimit>,
+    ) -> Self {
+        let provider: CredentialProvider = Arc::new(provider);
+        self.push(name, KeySource::Provider(provider), None, rate_limit)
+    }
+
+    fn push(
+        mut self,
+        name: &str,
+        source: KeySource,
+        key: Option<ApiKey>,
+        rate_limit: Option<RateLimit>,
+    ) -> Self {
+        self.keys.push(PooledKey {
+            name: name.to_string(),
+            source,
+            rate_limit,
+        });
+        self.state.get_mut().unwrap().keys.push(KeyState {
+            key,
+            stats: KeyStats {
+                name: name.to_string(),
+                ..KeyStats::default()
+            },
+            ..KeyState::default()
+        });
+        self
+    }
+
+    // How long a key stays disabled after an auth or quota error.
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    // How long a key stays disabled after a rate limit error.
+    pub fn with_rate_limit_cooldown(mut self, cooldown: Duration) -> Self {
+        self.rate_limit_cooldown = cooldown;
+        self
+    }
+
+    pub fn with_refresh_interval(mut self, interval: Duration) -> Self {
+        self.refresh_interval = Some(interval);
+        self
+    }
+
+    // Reloads every provider-backed key and re-enables keys that were
+    // disabled, e.g. after rotating credentials. Every provider is asked even
+    // when some fail; a key whose provider fails keeps its previous value and
+    // its error is returned with the key's name.
+    pub fn refresh(&self) -> Result<(), Vec<(String, SecretError)>> {
+        // Providers may block, e.g. on a vault request, so they are called
+        // without holding the lock.
+        let loaded: Vec<(usize, Result<ApiKey, SecretError>)> = self
+            .keys
+            .iter()
+            .enumerate()
+            .filter_map(|(index, key)| match &key.source {
+                KeySource::Provider(provider) => Some((index, provider())),
+                KeySource::Static => None,
+            })
+            .collect();
+        let now = Instant::now();
+        let mut errors = Vec::new();
+        let mut state = self.state.lock().unwrap();
+        for key_state in &mut state.keys {
+            key_state.disabled_until = None;
+        }
+        for (index, result) in loaded {
+            let key_state = &mut state.keys[index];
+            match result {
+                Ok(api_key) => {
+                    key_state.key = Some(api_key);
+                    key_state.fetched_at = Some(now);
+                }
+                Err(error) => {
+                    key_state.stats.errors += 1;
+                    errors.push((self.keys[index].name.clone(), error));
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    // The provider of a key that is missing or stale and has to be loaded
+    // before it is handed out.
+    fn stale_provider(
+        &self,
+        key: &PooledKey,
+        key_state: &KeyState,
+        now: Instant,
+    ) -> Option<CredentialProvider> {
+        let KeySource::Provider(provider) = &key.source else {
+            return None;
+        };
+        let stale = key_state.fetched_at.is_some_and(|fetched_at| {
+            self.refresh_interval
+                .is_some_and(|interval| now.saturating_duration_since(fetched_at) >= interval)
+        });
+        if (key_state.key.is_some() && !stale) || key_state.disabled_until.is_some() {
+            return None;
+        }
+        Some(provider.clone())
+    }
+
+    // Stores the result of a provider call. A provider that fails disables
+    // its key for the cooldown.
+    fn store(
+        &self,
+        key: &PooledKey,
+        key_state: &mut KeyState,
+        loaded: Result<ApiKey, SecretError>,
+        now: Instant,
+    ) {
+        match loaded {
+            Ok(api_key) => {
+                key_state.key = Some(api_key);
+                key_state.fetched_at = Some(now);
+            }
+            Err(error) => {
+                tracing::warn!(key = %key.name, error = ?error, "credential provider failed");
+                key_state.key = None;
+                key_state.stats.errors += 1;
+                key_state.disabled_until = Some(now + self.cooldown);
+                key_state.stats.times_disabled += 1;
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    // Picks a key that is neither disabled nor at its rate limit. When none
+    // is available, returns how long until the first one frees up.
+    pub fn acquire(&self) -> Result<KeyLease, Duration> {
+        let now = Instant::now();
+        let stale: Vec<(usize, CredentialProvider)> = {
+            let mut state = self.state.lock().unwrap();
+            self.keys
+                .iter()
+                .zip(state.keys.iter_mut())
+                .enumerate()
+                .filter_map(|(index, (key, key_state))| {
+                    key_state.refresh(now, key.rate_limit);
+                    self.stale_provider(key, key_state, now)
+                        .map(|provider| (index, provider))
+                })
+                .collect()
+        };
+        // Providers are called without holding the lock, as in `refresh`.
+        let loaded: Vec<(usize, Result<ApiKey, SecretError>)> = stale
+            .into_iter()
+            .map(|(index, provider)| (index, provider()))
+            .collect();
+
+        let mut state = self.state.lock().unwrap();
+        for (index, result) in loaded {
+            self.store(&self.keys[index], &mut state.keys[index], result, now);
+        }
+        let waits: Vec<Duration> = self
+            .keys
+            .iter()
+            .zip(&state.keys)
+            .map(|(key, key_state)| key_state.wait(now, key.rate_limit))
+            .collect();
+        let available = |i: &usize| waits[*i].is_zero();
+
+        let count = self.keys.len();
+        let index = match self.selection {
+            KeySelection::RoundRobin => (0..count)
+                .map(|offset| (state.next + offset) % count)
+                .find(available),
+            KeySelection::LeastLoaded => (0..count).filter(available).min_by_key(|i| {
+                (
+                    state.keys[*i].stats.in_flight,
+                    state.keys[*i].window.len(),
+                    *i,
+                )
+            }),
+        };
+        let Some(index) = index else {
+            return Err(waits.into_iter().min().unwrap_or(self.cooldown));
+        };
+
+        state.next = (index + 1) % count;
+        let key_state = &mut state.keys[index];
+        if self.keys[index].rate_limit.is_some() {
+            key_state.window.push_back(now);
+        }
+        key_state.stats.requests += 1;
+        key_state.stats.in_flight += 1;
+        Ok(KeyLease {
+            index,
+            name: self.keys[index].name.clone(),
+            key: key_state.key.clone().unwrap_or_else(|| ApiKey::new("")),
+        })
+    }
+
+    pub fn release(&self, lease: &KeyLease, outcome: KeyOutcome) {
+        let mut state = self.state.lock().unwrap();
+        let key_state = &mut state.keys[lease.index];
+        key_state.stats.in_flight = key_state.stats.in_flight.saturating_sub(1);
+        match outcome {
+            KeyOutcome::Success => return,
+            KeyOutcome::AuthError => key_state.stats.auth_errors += 1,
+            KeyOutcome::RateLimited => key_state.stats.rate_limit_errors += 1,
+            KeyOutcome::QuotaError => key_state.stats.quota_errors += 1,
+            KeyOutcome::Error => {}
+        }
+        key_state.stats.errors += 1;
+        let cooldown = match outcome {
+            KeyOutcome::AuthError | KeyOutcome::QuotaError => Some(self.cooldown),
+            KeyOutcome::RateLimited => Some(self.rate_limit_cooldown),
+            _ => None,
+        };
+        if let Some(cooldown) = cooldown {
+            key_state.disabled_until = Some(Instant::now() + cooldown);
+            key_state.stats.times_disabled += 1;
+        }
+        if outcome == KeyOutcome::AuthError
+            && matches!(self.keys[lease.index].source, KeySource::Provider(_))
+        {
+            key_state.key = None;
+        }
+    }
+
+    pub fn stats(&self) -> Vec<KeyStats> {
+        let now = Instant::now();
+        let state = self.state.lock().unwrap();
+        state
+            .keys
+            .iter()
+            .map(|key_state| KeyStats {
+                disabled: key_state.disabled_until.is_some_and(|until| until > now),
+                ..key_state.stats.clone()
+            })
+            .collect()
+    }
+}
+
+impl From<ApiKey> for KeyPool {
+    fn from(api_key: ApiKey) -> Self {
+        KeyPool::new(KeySelection::RoundRobin).key("default", api_key, None)
+    }
+}
+
+impl From<String> for KeyPool {
+    fn from(api_key: String) -> Self {
+        KeyPool::from(ApiKey::from(api_key))
+    }
+}
+
+impl From<&str> for KeyPool {
+    fn from(api_key: &str) -> Self {
+        KeyPool::from(ApiKey::from(api_key))
+    }
+}
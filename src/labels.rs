@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+
+use crate::oklink::{
+    BatchAddressTokenBalances, BatchInternalTransactionList, BatchNormalTransactionList,
+    BatchTokenTransactionList, BatchTransactionDetails, BlockTransactionList,
+    InternalTransactionList, LargeTransactionList, NativeTokenRanking, NftInventory,
+    NftTransactionList, NormalTransactionList, Oklink, RichList, TokenPositionList,
+    TokenTransactionList, TokenTransferList, TransactionDetails,
+};
+use crate::transport::TransportError;
+
+// OKLink's Explorer API reference limits the `address` parameter of
+// /api/v5/explorer/address/entity-labels to 20 comma-separated addresses;
+// `batch_address_entity_labels` rejects longer lists.
+const LABELS_BATCH: usize = 20;
+
+// Words that identify centralized exchange wallets in entity labels. They are
+// matched as whole words, so "gate" matches "Gate.io 3" but not "Aggregator".
+pub const EXCHANGE_KEYWORDS: &[&str] = &[
+    "exchange", "binance", "okx", "upbit", "bithumb", "coinone", "korbit", "coinbase", "kraken",
+    "bybit", "kucoin", "gate", "huobi", "htx",
+];
+
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+// Whether `label` contains any keyword as a whole word, or as a run of whole
+// words for keywords such as "crypto.com".
+pub fn matches_keywords<S: AsRef<str>>(label: &str, keywords: &[S]) -> bool {
+    let label = words(label);
+    keywords.iter().any(|keyword| {
+        let keyword = words(keyword.as_ref());
+        !keyword.is_empty()
+            && label
+                .windows(keyword.len())
+                .any(|window| window == keyword.as_slice())
+    })
+}
+
+// Typed responses whose addresses can be annotated with entity labels.
+pub trait HasAddresses {
+    fn addresses(&self) -> Vec<&str>;
+}
+
+macro_rules! impl_has_addresses {
+    // Batch responses hold one page per requested address.
+    ($ty:ty, $($pages:ident).+ [] $($list:ident).+ => $($field:ident),+) => {
+        impl HasAddresses for $ty {
+            fn addresses(&self) -> Vec<&str> {
+                self.$($pages).+
+                    .iter()
+                    .flat_map(|page| page.$($list).+.iter())
+                    .flat_map(|item| [$(item.$field.as_str()),+])
+                    .collect()
+            }
+        }
+    };
+    ($ty:ty, $($list:ident).+ => $($field:ident),+) => {
+        impl HasAddresses for $ty {
+            fn addresses(&self) -> Vec<&str> {
+                self.$($list).+
+                    .iter()
+                    .flat_map(|item| [$(item.$field.as_str()),+])
+                    .collect()
+            }
+        }
+    };
+}
+
+impl_has_addresses!(TokenTransactionList, data.transaction_lists => from, to);
+impl_has_addresses!(TokenTransferList, data.transaction_list => from, to);
+impl_has_addresses!(LargeTransactionList, data.transaction_list => from, to);
+impl_has_addresses!(NftTransactionList, data.transaction_list => from, to);
+impl_has_addresses!(NormalTransactionList, data.transaction_list => from, to);
+impl_has_addresses!(BlockTransactionList, data.block_list => from, to);
+impl_has_addresses!(BatchTransactionDetails, data => from, to);
+impl_has_addresses!(TransactionDetails, data => from, to);
+impl_has_addresses!(TokenPositionList, data.position_list => holder_address);
+impl_has_addresses!(RichList, data => holder_address);
+impl_has_addresses!(NativeTokenRanking, data.position_list => holder_address);
+impl_has_addresses!(InternalTransactionList, data.transaction_list => from, to);
+impl_has_addresses!(BatchNormalTransactionList, data[] transaction_list => from, to);
+impl_has_addresses!(BatchInternalTransactionList, data[] transaction_list => from, to);
+impl_has_addresses!(BatchTokenTransactionList, data[] transaction_list => from, to);
+impl_has_addresses!(BatchAddressTokenBalances, data.balance_list => address, token_contract_address);
+impl_has_addresses!(NftInventory, data.token_list => token_contract_address);
+
+// A response together with the entity labels of the addresses it contains.
+// Labels are keyed by lowercased address; unlabelled addresses are absent.
+#[derive(Debug)]
+pub struct Labeled<T> {
+    pub data: T,
+    pub labels: HashMap<String, String>,
+}
+
+impl<T> Labeled<T> {
+    pub fn label(&self, address: &str) -> Option<&str> {
+        self.labels.get(&address.to_lowercase()).map(String::as_str)
+    }
+}
+
+impl Oklink {
+    // Looks up entity labels for many addresses at once. Results, including
+    // addresses without a label, are cached for the lifetime of the client so
+    // only unseen addresses hit the API. Error responses are not cached.
+    pub async fn entity_labels(
+        &self,
+        addresses: &[&str],
+    ) -> Result<HashMap<String, String>, TransportError> {
+        let mut wanted: Vec<String> = addresses
+            .iter()
+            .filter(|address| !address.is_empty())
+            .map(|address| address.to_lowercase())
+            .collect();
+        wanted.sort_unstable();
+        wanted.dedup();
+
+        let missing: Vec<String> = {
+            let cache = self.entity_labels.lock().unwrap();
+            wanted
+                .iter()
+                .filter(|address| !cache.contains_key(*address))
+                .cloned()
+                .collect()
+        };
+        for chunk in missing.chunks(LABELS_BATCH) {
+            let chunk_refs: Vec<&str> = chunk.iter().map(String::as_str).collect();
+            let response = self.batch_address_entity_labels(&chunk_refs).await?;
+            if response.code != "0" {
+                return Err(TransportError::Api {
+                    code: response.code,
+                    msg: response.msg,
+                });
+            }
+            let mut cache = self.entity_labels.lock().unwrap();
+            for address in chunk {
+                cache.entry(address.clone()).or_insert(None);
+            }
+            for entry in response.data {
+                if entry.label.is_empty() {
+                    continue;
+                }
+                let slot = cache.entry(entry.address.to_lowercase()).or_insert(None);
+                if slot.is_none() {
+                    *slot = Some(entry.label);
+                }
+            }
+        }
+
+        let cache = self.entity_labels.lock().unwrap();
+        Ok(wanted
+            .into_iter()
+            .filter_map(|address| {
+                let label = cache.get(&address).cloned().flatten()?;
+                Some((address, label))
+            })
+            .collect())
+    }
+
+    pub async fn with_labels<T: HasAddresses>(
+        &self,
+        response: T,
+    ) -> Result<Labeled<T>, TransportError> {
+        let labels = self.entity_labels(&response.addresses()).await?;
+        Ok(Labeled {
+            data: response,
+            labels,
+        })
+    }
+}
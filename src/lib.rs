@@ -0,0 +1,32 @@
+pub mod alert;
+pub mod amount;
+pub mod analytics;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub mod blocking;
+mod clock;
+pub mod feed;
+pub mod fees;
+pub mod graph;
+pub mod kaia;
+pub mod keys;
+pub mod labels;
+#[cfg(all(feature = "nft-metadata", not(target_arch = "wasm32")))]
+pub mod metadata;
+pub mod middleware;
+pub mod nft;
+pub mod oklink;
+pub mod portfolio;
+#[cfg(feature = "prometheus")]
+pub mod prometheus;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ranking;
+pub mod secret;
+mod single_flight;
+pub mod transfer;
+pub mod transport;
+pub mod types;
+pub mod valuation;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod webhook;
+
+pub use oklink::Oklink;
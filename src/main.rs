@@ -1,9 +1,12 @@
-mod oklink;
-
+#[cfg(not(target_arch = "wasm32"))]
+use oklink::types::ProtocolType;
+#[cfg(not(target_arch = "wasm32"))]
 use oklink::Oklink;
-use tokio;
-use types::ProtocolType;
 
+#[cfg(target_arch = "wasm32")]
+fn main() {}
+
+#[cfg(not(target_arch = "wasm32"))]
 #[tokio::main]
 async fn main() {
     let api_key = "YOUR_API_KEY";
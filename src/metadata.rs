@@ -0,0 +1,290 @@
+use base64::Engine;
+use reqwest::Client;
+use serde::Deserialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+const DEFAULT_IPFS_GATEWAYS: [&str; 3] = [
+    "https://ipfs.io/ipfs/",
+    "https://dweb.link/ipfs/",
+    "https://gateway.pinata.cloud/ipfs/",
+];
+const DEFAULT_ARWEAVE_GATEWAY: &str = "https://arweave.net/";
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+pub enum MetadataError {
+    Http(reqwest::Error),
+    Json(serde_json::Error),
+    Base64(base64::DecodeError),
+    Status(reqwest::StatusCode),
+    TooLarge(usize),
+    UnsupportedUri(String),
+    // Not a decimal or 0x-prefixed hex uint256.
+    InvalidTokenId(String),
+}
+
+impl From<reqwest::Error> for MetadataError {
+    fn from(e: reqwest::Error) -> Self {
+        MetadataError::Http(e)
+    }
+}
+
+impl From<serde_json::Error> for MetadataError {
+    fn from(e: serde_json::Error) -> Self {
+        MetadataError::Json(e)
+    }
+}
+
+impl From<base64::DecodeError> for MetadataError {
+    fn from(e: base64::DecodeError) -> Self {
+        MetadataError::Base64(e)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NftAttribute {
+    pub trait_type: Option<String>,
+    pub value: serde_json::Value,
+    pub display_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NftMetadata {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    pub external_url: Option<String>,
+    pub animation_url: Option<String>,
+    #[serde(default)]
+    pub attributes: Vec<NftAttribute>,
+}
+
+pub struct MetadataResolver {
+    client: Client,
+    ipfs_gateways: Vec<String>,
+    arweave_gateway: String,
+    max_bytes: usize,
+    cache_capacity: usize,
+    cache: Mutex<(HashMap<String, NftMetadata>, VecDeque<String>)>,
+}
+
+impl Default for MetadataResolver {
+    fn default() -> Self {
+        MetadataResolver::new()
+    }
+}
+
+impl MetadataResolver {
+    pub fn new() -> Self {
+        MetadataResolver {
+            client: client(DEFAULT_TIMEOUT),
+            ipfs_gateways: DEFAULT_IPFS_GATEWAYS
+                .iter()
+                .map(|g| g.to_string())
+                .collect(),
+            arweave_gateway: DEFAULT_ARWEAVE_GATEWAY.to_string(),
+            max_bytes: 1024 * 1024,
+            cache_capacity: 1024,
+            cache: Mutex::new((HashMap::new(), VecDeque::new())),
+        }
+    }
+
+    pub fn with_ipfs_gateways(mut self, gateways: Vec<String>) -> Self {
+        self.ipfs_gateways = gateways;
+        self
+    }
+
+    pub fn with_arweave_gateway(mut self, gateway: &str) -> Self {
+        self.arweave_gateway = gateway.to_string();
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client = client(timeout);
+        self
+    }
+
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    pub fn with_cache_capacity(mut self, capacity: usize) -> Self {
+        self.cache_capacity = capacity;
+        self
+    }
+
+    // Maps `ipfs://` and `ar://` URIs onto HTTP gateway URLs, in the order they
+    // should be tried. HTTP(S) URIs are returned unchanged.
+    pub fn gateway_urls(&self, uri: &str) -> Vec<String> {
+        if let Some(path) = uri.strip_prefix("ipfs://") {
+            let path = path.strip_prefix("ipfs/").unwrap_or(path);
+            self.ipfs_gateways
+                .iter()
+                .map(|gateway| format!("{}{}", gateway, path))
+                .collect()
+        } else if let Some(path) = uri.strip_prefix("ar://") {
+            vec![format!("{}{}", self.arweave_gateway, path)]
+        } else if uri.starts_with("http://") || uri.starts_with("https://") {
+            vec![uri.to_string()]
+        } else {
+            Vec::new()
+        }
+    }
+
+    // KIP-37 URIs may contain an `{id}` placeholder for the token ID as 64
+    // lowercase hex digits.
+    pub async fn resolve_token(
+        &self,
+        token_uri: &str,
+        token_id: &str,
+    ) -> Result<NftMetadata, MetadataError> {
+        if !token_uri.contains("{id}") {
+            return self.resolve(token_uri).await;
+        }
+        let id = token_id_hex(token_id)
+            .ok_or_else(|| MetadataError::InvalidTokenId(token_id.to_string()))?;
+        self.resolve(&token_uri.replace("{id}", &id)).await
+    }
+
+    pub async fn resolve(&self, token_uri: &str) -> Result<NftMetadata, MetadataError> {
+        let token_uri = token_uri.trim();
+        if let Some(metadata) = self.cache.lock().unwrap().0.get(token_uri) {
+            return Ok(metadata.clone());
+        }
+
+        let metadata = if token_uri.starts_with("data:") {
+            self.parse(&decode_data_uri(token_uri)?)?
+        } else {
+            let urls = self.gateway_urls(token_uri);
+            if urls.is_empty() {
+                return Err(MetadataError::UnsupportedUri(token_uri.to_string()));
+            }
+            let mut last_error = None;
+            let mut body = None;
+            for url in urls {
+                match self.fetch(&url).await {
+                    Ok(bytes) => {
+                        body = Some(bytes);
+                        break;
+                    }
+                    Err(e) => last_error = Some(e),
+                }
+            }
+            match body {
+                Some(body) => self.parse(&body)?,
+                None => return Err(last_error.unwrap()),
+            }
+        };
+
+        let mut cache = self.cache.lock().unwrap();
+        if self.cache_capacity > 0 {
+            if cache.1.len() >= self.cache_capacity {
+                if let Some(oldest) = cache.1.pop_front() {
+                    cache.0.remove(&oldest);
+                }
+            }
+            cache.1.push_back(token_uri.to_string());
+            cache.0.insert(token_uri.to_string(), metadata.clone());
+        }
+        Ok(metadata)
+    }
+
+    fn parse(&self, body: &[u8]) -> Result<NftMetadata, MetadataError> {
+        if body.len() > self.max_bytes {
+            return Err(MetadataError::TooLarge(body.len()));
+        }
+        Ok(serde_json::from_slice(body)?)
+    }
+
+    async fn fetch(&self, url: &str) -> Result<Vec<u8>, MetadataError> {
+        let mut response = self.client.get(url).send().await?;
+        if !response.status().is_success() {
+            return Err(MetadataError::Status(response.status()));
+        }
+        if let Some(length) = response.content_length() {
+            if length as usize > self.max_bytes {
+                return Err(MetadataError::TooLarge(length as usize));
+            }
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            body.extend_from_slice(&chunk);
+            if body.len() > self.max_bytes {
+                return Err(MetadataError::TooLarge(body.len()));
+            }
+        }
+        Ok(body)
+    }
+}
+
+fn client(timeout: Duration) -> Client {
+    Client::builder()
+        .timeout(timeout)
+        .build()
+        .expect("reqwest client with timeout")
+}
+
+// Token IDs are uint256, so a decimal ID can exceed u128. Converts it to 64
+// hex digits by long multiplication over 32 big-endian bytes.
+pub fn token_id_hex(token_id: &str) -> Option<String> {
+    let token_id = token_id.trim();
+    if let Some(hex) = token_id
+        .strip_prefix("0x")
+        .or_else(|| token_id.strip_prefix("0X"))
+    {
+        if hex.is_empty() || hex.len() > 64 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        return Some(format!("{:0>64}", hex.to_lowercase()));
+    }
+    if token_id.is_empty() || !token_id.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let mut bytes = [0u8; 32];
+    for digit in token_id.bytes() {
+        let mut carry = u32::from(digit - b'0');
+        for byte in bytes.iter_mut().rev() {
+            let value = u32::from(*byte) * 10 + carry;
+            *byte = value as u8;
+            carry = value >> 8;
+        }
+        if carry != 0 {
+            return None;
+        }
+    }
+    Some(hex::encode(bytes))
+}
+
+fn decode_data_uri(uri: &str) -> Result<Vec<u8>, MetadataError> {
+    let rest = &uri["data:".len()..];
+    let (header, data) = rest
+        .split_once(',')
+        .ok_or_else(|| MetadataError::UnsupportedUri(uri.to_string()))?;
+    if header.split(';').any(|part| part == "base64") {
+        Ok(base64::engine::general_purpose::STANDARD.decode(data.trim())?)
+    } else {
+        Ok(percent_decode(data))
+    }
+}
+
+fn percent_decode(data: &str) -> Vec<u8> {
+    let bytes = data.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    decoded
+}
@@ -0,0 +1,264 @@
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::transport::{HttpMethod, HttpRequest, HttpResponse, TransportError};
+
+// Hooks run around every request the client sends. `before_request` runs in
+// the order middlewares were added; `after_response` and `on_error` run in
+// reverse, so the first middleware sees the final response.
+pub trait Middleware: Send + Sync {
+    fn before_request(&self, _request: &mut HttpRequest) {}
+
+    fn after_response(
+        &self,
+        _request: &HttpRequest,
+        _response: &mut HttpResponse,
+        _elapsed: Duration,
+    ) {
+    }
+
+    fn on_error(&self, _request: &HttpRequest, _error: &TransportError, _elapsed: Duration) {}
+
+    // Runs once per API request with the time it spent waiting for a key
+    // that was neither disabled nor at its rate limit; zero when one was
+    // free. `url` is the request URL before `before_request` rewrites it.
+    fn on_key_wait(&self, _url: &str, _waited: Duration) {}
+}
+
+// Path of the request URL without scheme, host or query, e.g.
+// "/api/v5/explorer/address/address-summary".
+pub fn endpoint_of(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let path = rest.find('/').map_or("/", |i| &rest[i..]);
+    path.split('?').next().unwrap_or(path)
+}
+
+// The `code` field of an OKLink response body; "0" means success.
+pub fn api_code(body: &[u8]) -> Option<String> {
+    #[derive(Deserialize)]
+    struct ApiStatus {
+        code: Option<Value>,
+    }
+    match serde_json::from_slice::<ApiStatus>(body).ok()?.code? {
+        Value::String(code) => Some(code),
+        code => Some(code.to_string()),
+    }
+}
+
+fn method_name(method: HttpMethod) -> &'static str {
+    match method {
+        HttpMethod::Get => "GET",
+        HttpMethod::Post => "POST",
+    }
+}
+
+// Adds fixed headers to every request, replacing any with the same name.
+#[derive(Debug, Clone, Default)]
+pub struct HeaderInjector {
+    headers: Vec<(String, String)>,
+}
+
+impl HeaderInjector {
+    pub fn new() -> Self {
+        HeaderInjector::default()
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+impl Middleware for HeaderInjector {
+    fn before_request(&self, request: &mut HttpRequest) {
+        for (name, value) in &self.headers {
+            request
+                .headers
+                .retain(|(existing, _)| !existing.eq_ignore_ascii_case(name));
+            request.headers.push((name.clone(), value.clone()));
+        }
+    }
+}
+
+type LogSink = Box<dyn Fn(&str) + Send + Sync>;
+
+// Logs one line per request. Headers are never logged since they carry the
+// API key; bodies are only logged when enabled. Without a sink, lines are
+// emitted as `tracing` events: request lines at info level, bodies at debug.
+#[derive(Default)]
+pub struct RequestLogger {
+    log_bodies: bool,
+    sink: Option<LogSink>,
+}
+
+impl RequestLogger {
+    pub fn new() -> Self {
+        RequestLogger::default()
+    }
+
+    pub fn with_bodies(mut self, log_bodies: bool) -> Self {
+        self.log_bodies = log_bodies;
+        self
+    }
+
+    pub fn with_sink(mut self, sink: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        self.sink = Some(Box::new(sink));
+        self
+    }
+
+    fn log(&self, line: &str) {
+        match &self.sink {
+            Some(sink) => sink(line),
+            None => tracing::info!("{}", line),
+        }
+    }
+
+    fn log_body(&self, line: &str) {
+        match &self.sink {
+            Some(sink) => sink(line),
+            None => tracing::debug!("{}", line),
+        }
+    }
+
+    fn describe(request: &HttpRequest) -> String {
+        let query: Vec<String> = request
+            .query
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        if query.is_empty() {
+            format!("{} {}", method_name(request.method), request.url)
+        } else {
+            format!(
+                "{} {}?{}",
+                method_name(request.method),
+                request.url,
+                query.join("&")
+            )
+        }
+    }
+}
+
+impl Middleware for RequestLogger {
+    fn before_request(&self, request: &mut HttpRequest) {
+        if !self.log_bodies {
+            return;
+        }
+        if let Some(body) = &request.body {
+            self.log_body(&format!(
+                "{} request body: {}",
+                RequestLogger::describe(request),
+                String::from_utf8_lossy(body)
+            ));
+        }
+    }
+
+    fn after_response(
+        &self,
+        request: &HttpRequest,
+        response: &mut HttpResponse,
+        elapsed: Duration,
+    ) {
+        self.log(&format!(
+            "{} -> {} ({} ms, {} bytes)",
+            RequestLogger::describe(request),
+            response.status,
+            elapsed.as_millis(),
+            response.body.len()
+        ));
+        if self.log_bodies {
+            self.log_body(&format!(
+                "response body: {}",
+                String::from_utf8_lossy(&response.body)
+            ));
+        }
+    }
+
+    fn on_error(&self, request: &HttpRequest, error: &TransportError, elapsed: Duration) {
+        self.log(&format!(
+            "{} failed after {} ms: {:?}",
+            RequestLogger::describe(request),
+            elapsed.as_millis(),
+            error
+        ));
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EndpointMetrics {
+    pub requests: u64,
+    pub errors: u64,
+    // Responses with a 4xx or 5xx status.
+    pub error_statuses: u64,
+    pub bytes_received: u64,
+    pub total_time: Duration,
+}
+
+// Per-endpoint request counters. Clones share the same counters, so keep a
+// clone to read them after handing one to the client.
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    endpoints: Arc<Mutex<HashMap<String, EndpointMetrics>>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Metrics::default()
+    }
+
+    pub fn snapshot(&self) -> HashMap<String, EndpointMetrics> {
+        self.endpoints.lock().unwrap().clone()
+    }
+
+    pub fn total(&self) -> EndpointMetrics {
+        self.endpoints.lock().unwrap().values().fold(
+            EndpointMetrics::default(),
+            |mut total, endpoint| {
+                total.requests += endpoint.requests;
+                total.errors += endpoint.errors;
+                total.error_statuses += endpoint.error_statuses;
+                total.bytes_received += endpoint.bytes_received;
+                total.total_time += endpoint.total_time;
+                total
+            },
+        )
+    }
+
+    fn record(
+        &self,
+        request: &HttpRequest,
+        elapsed: Duration,
+        update: impl FnOnce(&mut EndpointMetrics),
+    ) {
+        let mut endpoints = self.endpoints.lock().unwrap();
+        let metrics = endpoints
+            .entry(endpoint_of(&request.url).to_string())
+            .or_default();
+        metrics.requests += 1;
+        metrics.total_time += elapsed;
+        update(metrics);
+    }
+}
+
+impl Middleware for Metrics {
+    fn after_response(
+        &self,
+        request: &HttpRequest,
+        response: &mut HttpResponse,
+        elapsed: Duration,
+    ) {
+        self.record(request, elapsed, |metrics| {
+            metrics.bytes_received += response.body.len() as u64;
+            if response.status >= 400 {
+                metrics.error_statuses += 1;
+            }
+        });
+    }
+
+    fn on_error(&self, request: &HttpRequest, _error: &TransportError, elapsed: Duration) {
+        self.record(request, elapsed, |metrics| metrics.errors += 1);
+    }
+}
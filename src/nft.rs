@@ -0,0 +1,191 @@
+use crate::oklink::{NftBalance, NftTransaction, Oklink};
+use crate::transport::TransportError;
+use crate::types::ProtocolType;
+
+const PAGE_LIMIT: &str = "50";
+
+#[derive(Debug)]
+pub enum NftError {
+    Http(TransportError),
+    // A KIP-37 amount that isn't a whole number.
+    InvalidAmount(String),
+}
+
+impl From<TransportError> for NftError {
+    fn from(e: TransportError) -> Self {
+        NftError::Http(e)
+    }
+}
+
+fn parse_amount(amount: &str) -> Result<u64, NftError> {
+    amount
+        .trim()
+        .parse()
+        .map_err(|_| NftError::InvalidAmount(amount.to_string()))
+}
+
+// KIP-17 tokens are unique, so holding one means owning it outright; KIP-37
+// tokens are semi-fungible and carry an amount per token ID.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NftHolding {
+    Kip17 {
+        token_contract_address: String,
+        token_id: String,
+    },
+    Kip37 {
+        token_contract_address: String,
+        token_id: String,
+        amount: u64,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NftTransfer {
+    Kip17 {
+        tx_id: String,
+        height: String,
+        transaction_time: String,
+        from: String,
+        to: String,
+    },
+    Kip37 {
+        tx_id: String,
+        height: String,
+        transaction_time: String,
+        from: String,
+        to: String,
+        amount: u64,
+    },
+}
+
+impl NftHolding {
+    pub fn from_balance(
+        protocol_type: ProtocolType,
+        balance: &NftBalance,
+    ) -> Result<Self, NftError> {
+        Ok(match protocol_type {
+            ProtocolType::Token1155 => NftHolding::Kip37 {
+                token_contract_address: balance.token_contract_address.clone(),
+                token_id: balance.token_id.clone(),
+                amount: parse_amount(&balance.holding_amount)?,
+            },
+            _ => NftHolding::Kip17 {
+                token_contract_address: balance.token_contract_address.clone(),
+                token_id: balance.token_id.clone(),
+            },
+        })
+    }
+
+    pub fn token_id(&self) -> &str {
+        match self {
+            NftHolding::Kip17 { token_id, .. } | NftHolding::Kip37 { token_id, .. } => token_id,
+        }
+    }
+}
+
+impl NftTransfer {
+    pub fn from_transaction(tx: &NftTransaction) -> Result<Self, NftError> {
+        Ok(if tx.protocol_type == ProtocolType::Token1155.as_str() {
+            NftTransfer::Kip37 {
+                tx_id: tx.txid.clone(),
+                height: tx.height.clone(),
+                transaction_time: tx.transaction_time.clone(),
+                from: tx.from.clone(),
+                to: tx.to.clone(),
+                amount: parse_amount(&tx.amount)?,
+            }
+        } else {
+            NftTransfer::Kip17 {
+                tx_id: tx.txid.clone(),
+                height: tx.height.clone(),
+                transaction_time: tx.transaction_time.clone(),
+                from: tx.from.clone(),
+                to: tx.to.clone(),
+            }
+        })
+    }
+
+    pub fn to(&self) -> &str {
+        match self {
+            NftTransfer::Kip17 { to, .. } | NftTransfer::Kip37 { to, .. } => to,
+        }
+    }
+}
+
+// Successive owners of a token, oldest first. Expects the history in
+// chronological order, as returned by `nft_token_history`. A KIP-37 token ID
+// can have several holders at once, so every recipient of some units is
+// listed in the order they received them.
+pub fn ownership_chain(history: &[NftTransfer]) -> Vec<String> {
+    let mut owners: Vec<String> = Vec::new();
+    for transfer in history {
+        let to = transfer.to();
+        if owners.last().map(String::as_str) != Some(to) {
+            owners.push(to.to_string());
+        }
+    }
+    owners
+}
+
+impl Oklink {
+    pub async fn nft_holdings(
+        &self,
+        address: &str,
+        protocol_type: ProtocolType,
+        token_contract_address: Option<&str>,
+    ) -> Result<Vec<NftHolding>, NftError> {
+        let mut holdings = Vec::new();
+        let mut page = 1u32;
+        loop {
+            let page_str = page.to_string();
+            let response = self
+                .address_balance_details(
+                    address,
+                    protocol_type.as_str(),
+                    token_contract_address,
+                    Some(&page_str),
+                    Some(PAGE_LIMIT),
+                )
+                .await?;
+            for balance in &response.data.token_list {
+                holdings.push(NftHolding::from_balance(protocol_type, balance)?);
+            }
+            if page >= response.data.total_page.parse::<u32>().unwrap_or(1) {
+                return Ok(holdings);
+            }
+            page += 1;
+        }
+    }
+
+    pub async fn nft_token_history(
+        &self,
+        token_contract_address: &str,
+        token_id: &str,
+    ) -> Result<Vec<NftTransfer>, NftError> {
+        let mut transactions: Vec<NftTransaction> = Vec::new();
+        let mut page = 1u32;
+        loop {
+            let page_str = page.to_string();
+            let response = self
+                .nft_transaction_list(
+                    token_contract_address,
+                    Some(token_id),
+                    Some(&page_str),
+                    Some(PAGE_LIMIT),
+                )
+                .await?;
+            transactions.extend(response.data.transaction_list);
+            if page >= response.data.total_page.parse::<u32>().unwrap_or(1) {
+                break;
+            }
+            page += 1;
+        }
+        // The explorer returns the newest transfers first.
+        transactions.reverse();
+        transactions.sort_by_key(|tx| tx.height.parse::<u64>().unwrap_or(0));
+        transactions
+            .iter()
+            .map(NftTransfer::from_transaction)
+            .collect()
+    }
+}
@@ -1,83 +1,918 @@
-use reqwest::Client;
 use serde::Deserialize;
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::field::Empty;
+use tracing::Instrument;
+use crate::amount::TokenMeta;
+use crate::clock::{self, Instant};
+use crate::keys::{KeyLease, KeyOutcome, KeyPool, KeyStats};
+use crate::middleware::{api_code, Middleware};
+use crate::single_flight::SingleFlight;
+use crate::transport::{
+    HttpMethod, HttpRequest, HttpResponse, HttpTransport, ReqwestTransport, TransportError,
+};
 use crate::types::ProtocolType;
 
 const BASE_URL: &str = "https://www.oklink.com/";
 const CHAIN_SHORT_NAME: &str = "KLAYTN";
+const CHAIN_ID: &str = "8217";
+// Long enough to ride out a per-second rate limit, short of a disabled key's
+// cooldown.
+const DEFAULT_MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(30);
 
 #[derive(Debug, Deserialize)]
 pub struct AddressInformation {
-    code: String,
-    msg: String,
-    data: AddressData,
+    pub code: String,
+    pub msg: String,
+    pub data: AddressData,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct AddressData {
-    chain_full_name: String,
-    chain_short_name: String,
-    address: String,
-    contract_address: String,
-    balance: String,
-    balance_symbol: String,
-    transaction_count: String,
-    verifying: String,
-    send_amount: String,
-    receive_amount: String,
-    token_amount: String,
-    total_token_value: String,
-    create_contract_address: String,
-    create_contract_transaction_hash: String,
-    first_transaction_time: String,
-    last_transaction_time: String,
-    token: String,
-    bandwidth: String,
-    energy: String,
-    voting_rights: String,
-    unclaimed_voting_rewards: String,
-    is_aa_address: bool,
+    pub chain_full_name: String,
+    pub chain_short_name: String,
+    pub address: String,
+    pub contract_address: String,
+    pub balance: String,
+    pub balance_symbol: String,
+    pub transaction_count: String,
+    pub verifying: String,
+    pub send_amount: String,
+    pub receive_amount: String,
+    pub token_amount: String,
+    pub total_token_value: String,
+    pub create_contract_address: String,
+    pub create_contract_transaction_hash: String,
+    pub first_transaction_time: String,
+    pub last_transaction_time: String,
+    pub token: String,
+    pub bandwidth: String,
+    pub energy: String,
+    pub voting_rights: String,
+    pub unclaimed_voting_rewards: String,
+    pub is_aa_address: bool,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct TokenTransactionList {
+    pub code: String,
+    pub msg: String,
+    pub data: TokenTransactionListData,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenTransactionListData {
+    pub page: String,
+    pub limit: String,
+    pub total_page: String,
+    pub transaction_lists: Vec<TokenTransaction>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenTransaction {
+    pub tx_id: String,
+    pub block_hash: String,
+    pub height: String,
+    pub transaction_time: String,
+    pub from: String,
+    pub to: String,
+    pub token_contract_address: String,
+    pub token_id: String,
+    pub amount: String,
+    pub symbol: String,
+    pub is_from_contract: bool,
+    pub is_to_contract: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenTransferList {
+    pub code: String,
+    pub msg: String,
+    pub data: TokenTransferListData,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenTransferListData {
+    pub page: String,
+    pub limit: String,
+    pub total_page: String,
+    pub chain_full_name: String,
+    pub chain_short_name: String,
+    pub transaction_list: Vec<TokenTransfer>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenTransfer {
+    pub txid: String,
+    pub block_hash: String,
+    pub height: String,
+    pub transaction_time: String,
+    pub from: String,
+    pub to: String,
+    pub is_from_contract: bool,
+    pub is_to_contract: bool,
+    pub amount: String,
+    pub transaction_symbol: String,
+    pub method_id: String,
+    pub token_contract_address: String,
+    pub protocol_type: String,
+    pub state: String,
+    pub token_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LargeTransactionList {
+    pub code: String,
+    pub msg: String,
+    pub data: LargeTransactionListData,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LargeTransactionListData {
+    pub page: String,
+    pub limit: String,
+    pub total_page: String,
+    pub chain_full_name: String,
+    pub chain_short_name: String,
+    pub transaction_list: Vec<LargeTransaction>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LargeTransaction {
+    pub txid: String,
+    pub block_hash: String,
+    pub height: String,
+    pub transaction_time: String,
+    pub from: String,
+    pub to: String,
+    pub is_from_contract: bool,
+    pub is_to_contract: bool,
+    pub amount: String,
+    pub transaction_symbol: String,
+    pub txfee: String,
+    pub state: String,
+    pub token_id: String,
+    pub token_contract_address: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EntityLabels {
+    pub code: String,
+    pub msg: String,
+    pub data: Vec<EntityLabel>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EntityLabel {
+    pub label: String,
+    pub address: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RichList {
+    pub code: String,
+    pub msg: String,
+    pub data: Vec<RichListEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RichListEntry {
+    pub symbol: String,
+    pub holder_address: String,
+    pub amount: String,
+    pub rank: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NativeTokenRanking {
+    pub code: String,
+    pub msg: String,
+    pub data: NativeTokenRankingData,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NativeTokenRankingData {
+    pub page: String,
+    pub limit: String,
+    pub total_page: String,
+    pub position_list: Vec<RichListEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenList {
+    pub code: String,
+    pub msg: String,
+    pub data: TokenListData,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenListData {
+    pub page: String,
+    pub limit: String,
+    pub total_page: String,
+    pub token_list: Vec<TokenInfo>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenInfo {
+    pub token_full_name: String,
+    pub token: String,
+    pub precision: String,
+    pub token_contract_address: String,
+    pub protocol_type: String,
+    pub address_count: String,
+    pub total_supply: String,
+    pub circulating_supply: String,
+    pub price: String,
+    pub website: String,
+    pub total_market_cap: String,
+    pub issue_date: String,
+    pub transaction_amount_24h: String,
+    pub tvl: String,
+    pub logo_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddressTokenBalance {
+    pub code: String,
+    pub msg: String,
+    pub data: AddressTokenBalanceData,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddressTokenBalanceData {
+    pub page: String,
+    pub limit: String,
+    pub total_page: String,
+    pub token_list: Vec<TokenBalance>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenBalance {
+    pub symbol: String,
+    pub token_contract_address: String,
+    pub holding_amount: String,
+    pub price_usd: String,
+    pub value_usd: String,
+    pub token_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchAddressBalances {
+    pub code: String,
+    pub msg: String,
+    pub data: BatchAddressBalancesData,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchAddressBalancesData {
+    pub symbol: String,
+    pub balance_list: Vec<AddressBalance>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AddressBalance {
+    pub address: String,
+    pub balance: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchAddressTokenBalances {
+    pub code: String,
+    pub msg: String,
+    pub data: BatchAddressTokenBalancesData,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchAddressTokenBalancesData {
+    pub page: String,
+    pub limit: String,
+    pub total_page: String,
+    pub balance_list: Vec<AddressTokenHolding>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AddressTokenHolding {
+    pub address: String,
+    pub holding_amount: String,
+    pub token_contract_address: String,
+    pub token_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddressBalanceHistory {
+    pub code: String,
+    pub msg: String,
+    pub data: AddressBalanceHistoryData,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AddressBalanceHistoryData {
+    pub address: String,
+    pub height: String,
+    pub balance: String,
+    pub balance_symbol: String,
+    pub token_contract_address: String,
+    pub block_time: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenSupplyHistory {
+    pub code: String,
+    pub msg: String,
+    pub data: TokenSupplyHistoryData,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenSupplyHistoryData {
+    pub height: String,
+    pub total_supply: String,
+    pub block_time: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenPositionList {
+    pub code: String,
+    pub msg: String,
+    pub data: TokenPositionListData,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenPositionListData {
+    pub page: String,
+    pub limit: String,
+    pub total_page: String,
+    pub circulating_supply: String,
+    pub position_list: Vec<TokenPosition>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenPosition {
+    pub holder_address: String,
+    pub amount: String,
+    pub value_usd: String,
+    pub position_change_24h: String,
+    pub rank: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NftInventory {
+    pub code: String,
+    pub msg: String,
+    pub data: NftInventoryData,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NftInventoryData {
+    pub page: String,
+    pub limit: String,
+    pub total_page: String,
+    pub token_list: Vec<NftBalance>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NftBalance {
+    pub symbol: String,
+    pub token_contract_address: String,
+    pub token_type: String,
+    pub holding_amount: String,
+    pub token_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NftTransactionList {
+    pub code: String,
+    pub msg: String,
+    pub data: NftTransactionListData,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NftTransactionListData {
+    pub page: String,
+    pub limit: String,
+    pub total_page: String,
+    pub transaction_list: Vec<NftTransaction>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NftTransaction {
+    pub txid: String,
+    pub block_hash: String,
+    pub height: String,
+    pub transaction_time: String,
+    pub from: String,
+    pub to: String,
+    pub token_contract_address: String,
+    pub token_id: String,
+    pub amount: String,
+    pub protocol_type: String,
+    pub method_id: String,
+    pub state: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NftCollectionInfo {
+    pub code: String,
+    pub msg: String,
+    pub data: NftCollectionInfoData,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NftCollectionInfoData {
+    pub collection_name: String,
+    pub token_contract_address: String,
+    pub protocol_type: String,
+    pub symbol: String,
+    pub total_supply: String,
+    pub holder_count: String,
+    pub transaction_count: String,
+    pub creator_address: String,
+    pub create_time: String,
+    pub logo_url: String,
+    pub website: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NftDetails {
+    pub code: String,
+    pub msg: String,
+    pub data: NftDetailsData,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NftDetailsData {
+    pub token_contract_address: String,
+    pub token_id: String,
+    pub protocol_type: String,
+    pub name: String,
+    pub token_uri: String,
+    pub image_url: String,
+    pub owner_address: String,
+    pub amount: String,
+    pub mint_time: String,
+    pub mint_txid: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenPriceList {
+    pub code: String,
+    pub msg: String,
+    pub data: Vec<TokenPrice>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenPrice {
+    pub chain_id: String,
+    pub token_contract_address: String,
+    pub last_price: String,
+    pub price_change_24h: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenPriceHistory {
+    pub code: String,
+    pub msg: String,
+    pub data: Vec<HistoricalPrice>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HistoricalPrice {
+    pub time: String,
+    pub price: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NormalTransactionList {
+    pub code: String,
+    pub msg: String,
+    pub data: NormalTransactionListData,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NormalTransactionListData {
+    pub page: String,
+    pub limit: String,
+    pub total_page: String,
+    pub transaction_list: Vec<NormalTransaction>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NormalTransaction {
+    pub tx_id: String,
+    pub method_id: String,
+    pub nonce: String,
+    pub gas_price: String,
+    pub gas_limit: String,
+    pub gas_used: String,
+    pub block_hash: String,
+    pub height: String,
+    pub transaction_time: String,
+    pub from: String,
+    pub to: String,
+    pub is_from_contract: bool,
+    pub is_to_contract: bool,
+    pub amount: String,
+    pub symbol: String,
+    pub tx_fee: String,
+    pub state: String,
+    pub transaction_type: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InternalTransactionList {
+    pub code: String,
+    pub msg: String,
+    pub data: InternalTransactionListData,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InternalTransactionListData {
+    pub page: String,
+    pub limit: String,
+    pub total_page: String,
+    pub transaction_list: Vec<InternalTransaction>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct InternalTransaction {
+    pub tx_id: String,
+    pub operation: String,
+    pub block_hash: String,
+    pub height: String,
+    pub transaction_time: String,
+    pub from: String,
+    pub to: String,
+    pub is_from_contract: bool,
+    pub is_to_contract: bool,
+    pub amount: String,
+    pub state: String,
+    pub symbol: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchNormalTransactionList {
+    pub code: String,
+    pub msg: String,
+    pub data: Vec<BatchNormalTransactionListData>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchNormalTransactionListData {
+    pub page: String,
+    pub limit: String,
+    pub total_page: String,
+    pub transaction_list: Vec<BatchNormalTransaction>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchNormalTransaction {
+    pub tx_id: String,
+    pub method_id: String,
+    pub block_hash: String,
+    pub height: String,
+    pub transaction_time: String,
+    pub from: String,
+    pub to: String,
+    pub is_from_contract: bool,
+    pub is_to_contract: bool,
+    pub amount: String,
+    pub symbol: String,
+    pub tx_fee: String,
+    pub state: String,
+    pub transaction_type: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchInternalTransactionList {
+    pub code: String,
+    pub msg: String,
+    pub data: Vec<BatchInternalTransactionListData>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchInternalTransactionListData {
+    pub page: String,
+    pub limit: String,
+    pub total_page: String,
+    pub transaction_list: Vec<BatchInternalTransaction>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchInternalTransaction {
+    pub tx_id: String,
+    pub block_hash: String,
+    pub height: String,
+    pub transaction_time: String,
+    pub operation: String,
+    pub from: String,
+    pub to: String,
+    pub is_from_contract: bool,
+    pub is_to_contract: bool,
+    pub amount: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchTokenTransactionList {
+    pub code: String,
+    pub msg: String,
+    pub data: Vec<BatchTokenTransactionListData>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchTokenTransactionListData {
+    pub page: String,
+    pub limit: String,
+    pub total_page: String,
+    pub transaction_list: Vec<TokenTransaction>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchTransactionDetails {
+    pub code: String,
+    pub msg: String,
+    pub data: Vec<TransactionDetail>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransactionDetail {
+    pub txid: String,
+    pub method_id: String,
+    pub block_hash: String,
+    pub height: String,
+    pub transaction_time: String,
+    pub from: String,
+    pub to: String,
+    pub is_from_contract: bool,
+    pub is_to_contract: bool,
+    pub amount: String,
+    pub symbol: String,
+    pub nonce: String,
+    pub txfee: String,
+    pub gas_price: String,
+    pub gas_limit: String,
+    pub gas_used: String,
+    pub state: String,
+    pub transaction_type: String,
+    pub fee_payer: Option<String>,
+    pub fee_ratio: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TransactionDetails {
+    pub code: String,
+    pub msg: String,
+    pub data: Vec<TransactionDetail>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BlockTransactionList {
+    pub code: String,
+    pub msg: String,
+    pub data: BlockTransactionListData,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BlockTransactionListData {
+    pub page: String,
+    pub limit: String,
+    pub total_page: String,
+    pub block_list: Vec<BlockTransaction>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BlockTransaction {
+    pub txid: String,
+    pub block_hash: String,
+    pub height: String,
+    pub transaction_time: String,
+    pub from: String,
+    pub to: String,
+    pub is_from_contract: bool,
+    pub is_to_contract: bool,
+    pub amount: String,
+    pub transaction_symbol: String,
+    pub txfee: String,
+    pub state: String,
+    pub token_id: String,
+    pub token_contract_address: String,
+    pub transaction_type: String,
+    pub fee_payer: Option<String>,
+    pub fee_ratio: Option<String>,
+}
+
+// An endpoint and its query parameters.
+type InFlightKey = (String, Vec<(String, String)>);
+
 pub struct Oklink {
-    api_key: String,
-    client: Client,
+    keys: KeyPool,
+    base_url: String,
+    transport: Arc<dyn HttpTransport>,
+    middleware: Vec<Box<dyn Middleware>>,
+    single_flight: bool,
+    max_rate_limit_wait: Duration,
+    in_flight: SingleFlight<InFlightKey, Arc<HttpResponse>>,
+    pub(crate) token_meta: Mutex<HashMap<String, TokenMeta>>,
+    pub(crate) daily_prices: Mutex<HashMap<(String, u64), Option<f64>>>,
+    pub(crate) entity_labels: Mutex<HashMap<String, Option<String>>>,
 }
 
 impl Oklink {
-    pub fn new(api_key: String) -> Self {
+    // Accepts a single API key or a `KeyPool`.
+    pub fn new(api_key: impl Into<KeyPool>) -> Self {
+        Oklink::with_transport(api_key, ReqwestTransport::new())
+    }
+
+    pub fn with_transport(
+        api_key: impl Into<KeyPool>,
+        transport: impl HttpTransport + 'static,
+    ) -> Self {
         Oklink {
-            api_key,
-            client: Client::new(),
+            keys: api_key.into(),
+            base_url: BASE_URL.to_string(),
+            transport: Arc::new(transport),
+            middleware: Vec::new(),
+            single_flight: true,
+            max_rate_limit_wait: DEFAULT_MAX_RATE_LIMIT_WAIT,
+            in_flight: SingleFlight::default(),
+            token_meta: Mutex::new(HashMap::new()),
+            daily_prices: Mutex::new(HashMap::new()),
+            entity_labels: Mutex::new(HashMap::new()),
         }
     }
 
-    fn headers(&self) -> HashMap<&str, &str> {
-        let mut headers = HashMap::new();
-        headers.insert("Content-Type", "application/json");
-        headers.insert("Ok-Access-Key", &self.api_key);
-        headers
+    // Sends API requests to another host, e.g. a proxy or a mock server.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = format!("{}/", base_url.trim_end_matches('/'));
+        self
+    }
+
+    pub fn with_middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.push(Box::new(middleware));
+        self
+    }
+
+    // Identical GET requests made while one is already in flight wait for it
+    // and share its response instead of hitting the API again. On by default.
+    pub fn with_single_flight(mut self, enabled: bool) -> Self {
+        self.single_flight = enabled;
+        self
+    }
+
+    // How long a request may wait for a key when every key is disabled or at
+    // its rate limit. Requests that would wait longer fail with
+    // `TransportError::RateLimited`; `Duration::ZERO` never waits.
+    pub fn with_max_rate_limit_wait(mut self, max_wait: Duration) -> Self {
+        self.max_rate_limit_wait = max_wait;
+        self
+    }
+
+    // Every request, including JSON-RPC calls to Kaia nodes, is sent through
+    // here so the middleware chain sees it.
+    pub(crate) async fn send(
+        &self,
+        mut request: HttpRequest,
+    ) -> Result<HttpResponse, TransportError> {
+        for middleware in &self.middleware {
+            middleware.before_request(&mut request);
+        }
+        let started = Instant::now();
+        match self.transport.send(request.clone()).await {
+            Ok(mut response) => {
+                let elapsed = started.elapsed();
+                for middleware in self.middleware.iter().rev() {
+                    middleware.after_response(&request, &mut response, elapsed);
+                }
+                Ok(response)
+            }
+            Err(error) => {
+                let elapsed = started.elapsed();
+                for middleware in self.middleware.iter().rev() {
+                    middleware.on_error(&request, &error, elapsed);
+                }
+                Err(error)
+            }
+        }
+    }
+
+    pub fn key_stats(&self) -> Vec<KeyStats> {
+        self.keys.stats()
+    }
+
+    fn headers(&self) -> Vec<(String, String)> {
+        vec![("Content-Type".to_string(), "application/json".to_string())]
     }
 
     async fn _get<T: for<'de> Deserialize<'de>>(
         &self,
         endpoint: &str,
         params: &[(&str, &str)],
-    ) -> Result<T, reqwest::Error> {
-        let url = format!("{}{}", BASE_URL, endpoint);
-        let response = self
-            .client
-            .get(&url)
-            .headers(self.headers().into())
-            .query(&params)
-            .send()
-            .await?
-            .json::<T>()
-            .await?;
+    ) -> Result<T, TransportError> {
+        let response = if self.single_flight {
+            // Requests are identified by endpoint and query; the key used to
+            // send them doesn't change the response.
+            let query = params
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect();
+            self.in_flight
+                .run((endpoint.to_string(), query), || async {
+                    self.fetch(endpoint, params).await.map(Arc::new)
+                })
+                .await?
+        } else {
+            Arc::new(self.fetch(endpoint, params).await?)
+        };
+        let response = serde_json::from_slice(&response.body)?;
+        Ok(response)
+    }
+
+    async fn acquire_key(&self, url: &str) -> Result<KeyLease, TransportError> {
+        let started = Instant::now();
+        let lease = loop {
+            match self.keys.acquire() {
+                Ok(lease) => break Ok(lease),
+                Err(retry_after)
+                    if started.elapsed() + retry_after > self.max_rate_limit_wait =>
+                {
+                    break Err(TransportError::RateLimited { retry_after });
+                }
+                Err(retry_after) => clock::sleep(retry_after).await,
+            }
+        };
+        let waited = started.elapsed();
+        for middleware in &self.middleware {
+            middleware.on_key_wait(url, waited);
+        }
+        lease
+    }
+
+    async fn fetch(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+    ) -> Result<HttpResponse, TransportError> {
+        let url = format!("{}{}", self.base_url, endpoint);
+        let lease = self.acquire_key(&url).await?;
+        let request = HttpRequest {
+            method: HttpMethod::Get,
+            url,
+            headers: self.headers(),
+            api_key: Some(lease.key.clone()),
+            query: params
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            body: None,
+        };
+        // The API key travels in a header, so the query can be recorded as is.
+        let query: Vec<String> = params
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        let chain = params
+            .iter()
+            .find(|(key, _)| *key == "chainShortName" || *key == "chainId")
+            .map_or("", |(_, value)| *value);
+        let span = tracing::debug_span!(
+            "oklink_request",
+            endpoint,
+            chain,
+            key = %lease.name,
+            params = %query.join("&"),
+            // Requests are not retried yet.
+            attempt = 1u32,
+            latency_ms = Empty,
+            status = Empty,
+            code = Empty,
+        );
+        let started = Instant::now();
+        let response = self.send(request).instrument(span.clone()).await;
+        span.record("latency_ms", started.elapsed().as_millis() as u64);
+        let response = match response {
+            Ok(response) => response,
+            Err(error) => {
+                self.keys.release(&lease, KeyOutcome::Error);
+                tracing::warn!(parent: &span, error = ?error, "request failed");
+                return Err(error);
+            }
+        };
+        span.record("status", response.status);
+        let code = api_code(&response.body);
+        self.keys.release(
+            &lease,
+            KeyOutcome::classify(response.status, code.as_deref()),
+        );
+        if let Some(code) = code {
+            span.record("code", code.as_str());
+            if code != "0" {
+                tracing::warn!(parent: &span, code = %code, "API returned an error code");
+            }
+        }
         Ok(response)
     }
 
-    pub async fn address_info(&self, address: &str) -> Result<AddressInformation, reqwest::Error> {
+    pub async fn address_info(&self, address: &str) -> Result<AddressInformation, TransportError> {
         let params = [("chainShortName", CHAIN_SHORT_NAME), ("address", address)];
         self._get("api/v5/explorer/address/address-summary", &params).await
     }
@@ -85,32 +920,21 @@ impl Oklink {
     pub async fn evm_address_info(
         &self,
         address: &str,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<AddressInformation, TransportError> {
         let params = [("chainShortName", CHAIN_SHORT_NAME), ("address", address)];
-        let url = format!("{}api/v5/explorer/address/information-evm", BASE_URL);
-        let response = self
-            .client
-            .get(&url)
-            .headers(self.headers().into())
-            .query(&params)
-            .send()
-            .await?
-            .json::<AddressInformation>()
-            .await?;
-        Ok(response)
         self._get("api/v5/explorer/address/information-evm", &params).await
     }
 
-    pub async fn address_active_chain(&self, address: &str) -> Result<AddressInformation, reqwest::Error> {
+    pub async fn address_active_chain(&self, address: &str) -> Result<AddressInformation, TransportError> {
         let params = [("chainShortName", CHAIN_SHORT_NAME), ("address", address)];
         self._get("api/v5/explorer/address/address-active-chain", &params).await
     }
 
-    pub async fn address_token_balance(&self, address: &str, protocol_type: ProtocolType, token_contract_address: Option<&str>, page: Option<&str>, limit: Option<&str>) -> Result<AddressInformation, reqwest::Error> {
+    pub async fn address_token_balance(&self, address: &str, protocol_type: ProtocolType, token_contract_address: Option<&str>, page: Option<&str>, limit: Option<&str>) -> Result<AddressTokenBalance, TransportError> {
         let mut params = vec![
             ("chainShortName", CHAIN_SHORT_NAME),
             ("address", address),
-            ("protocolType", &serde_json::to_string(&protocol_type).unwrap()),
+            ("protocolType", protocol_type.as_str()),
         ];
         if let Some(token_contract_address) = token_contract_address {
             params.push(("tokenContractAddress", token_contract_address));
@@ -131,7 +955,7 @@ impl Oklink {
         token_contract_address: Option<&str>,
         page: Option<&str>,
         limit: Option<&str>,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<NftInventory, TransportError> {
         let mut params = vec![
             ("chainShortName", CHAIN_SHORT_NAME),
             ("address", address),
@@ -154,7 +978,7 @@ impl Oklink {
         address: &str,
         height: &str,
         token_contract_address: Option<&str>,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<AddressBalanceHistory, TransportError> {
         let mut params = vec![
             ("chainShortName", CHAIN_SHORT_NAME),
             ("address", address),
@@ -166,6 +990,7 @@ impl Oklink {
         self._get("api/v5/explorer/block/address-balance-history", &params).await
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn address_transaction_list(
         &self,
         address: &str,
@@ -176,7 +1001,7 @@ impl Oklink {
         is_from_or_to: Option<&str>,
         page: Option<&str>,
         limit: Option<&str>,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<AddressInformation, TransportError> {
         let mut params = vec![
             ("chainShortName", CHAIN_SHORT_NAME),
             ("address", address),
@@ -213,7 +1038,7 @@ impl Oklink {
         is_from_or_to: Option<&str>,
         page: Option<&str>,
         limit: Option<&str>,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<NormalTransactionList, TransportError> {
         let mut params = vec![("chainShortName", CHAIN_SHORT_NAME), ("address", address)];
         if let Some(start_block_height) = start_block_height {
             params.push(("startBlockHeight", start_block_height));
@@ -241,7 +1066,7 @@ impl Oklink {
         is_from_or_to: Option<&str>,
         page: Option<&str>,
         limit: Option<&str>,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<InternalTransactionList, TransportError> {
         let mut params = vec![("chainShortName", CHAIN_SHORT_NAME), ("address", address)];
         if let Some(start_block_height) = start_block_height {
             params.push(("startBlockHeight", start_block_height));
@@ -268,7 +1093,30 @@ impl Oklink {
         token_contract_address: Option<&str>,
         page: Option<&str>,
         limit: Option<&str>,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<TokenTransactionList, TransportError> {
+        self.address_token_transaction_list_by_height(
+            address,
+            protocol_type,
+            token_contract_address,
+            None,
+            None,
+            page,
+            limit,
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn address_token_transaction_list_by_height(
+        &self,
+        address: &str,
+        protocol_type: &str,
+        token_contract_address: Option<&str>,
+        start_block_height: Option<&str>,
+        end_block_height: Option<&str>,
+        page: Option<&str>,
+        limit: Option<&str>,
+    ) -> Result<TokenTransactionList, TransportError> {
         let mut params = vec![
             ("chainShortName", CHAIN_SHORT_NAME),
             ("address", address),
@@ -277,6 +1125,12 @@ impl Oklink {
         if let Some(token_contract_address) = token_contract_address {
             params.push(("tokenContractAddress", token_contract_address));
         }
+        if let Some(start_block_height) = start_block_height {
+            params.push(("startBlockHeight", start_block_height));
+        }
+        if let Some(end_block_height) = end_block_height {
+            params.push(("endBlockHeight", end_block_height));
+        }
         if let Some(page) = page {
             params.push(("page", page));
         }
@@ -289,24 +1143,41 @@ impl Oklink {
     pub async fn address_entity_labels(
         &self,
         address: &str,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<EntityLabels, TransportError> {
         let params = [("chainShortName", CHAIN_SHORT_NAME), ("address", address)];
         self._get("api/v5/explorer/address/entity-labels", &params).await
     }
 
+    pub async fn batch_address_entity_labels(
+        &self,
+        addresses: &[&str],
+    ) -> Result<EntityLabels, TransportError> {
+        if addresses.len() > 20 {
+            return Err(TransportError::InvalidArgument(
+                "The maximum number of addresses is 20".to_string(),
+            ));
+        }
+        let addresses = addresses.join(",");
+        let params = [
+            ("chainShortName", CHAIN_SHORT_NAME),
+            ("address", addresses.as_str()),
+        ];
+        self._get("api/v5/explorer/address/entity-labels", &params).await
+    }
+
     pub async fn batch_address_balances(
         &self,
         addresses: &[&str],
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<BatchAddressBalances, TransportError> {
         if addresses.len() > 100 {
-            return Err(reqwest::Error::new(
-                reqwest::StatusCode::BAD_REQUEST,
-                "The maximum number of addresses is 100",
+            return Err(TransportError::InvalidArgument(
+                "The maximum number of addresses is 100".to_string(),
             ));
         }
+        let addresses = addresses.join(",");
         let params = [
             ("chainShortName", CHAIN_SHORT_NAME),
-            ("addresses", &addresses.join(",")),
+            ("addresses", addresses.as_str()),
         ];
         self._get("api/v5/explorer/address/balance-multi", &params).await
     }
@@ -317,16 +1188,16 @@ impl Oklink {
         protocol_type: Option<&str>,
         page: Option<&str>,
         limit: Option<&str>,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<BatchAddressTokenBalances, TransportError> {
         if addresses.len() > 50 {
-            return Err(reqwest::Error::new(
-                reqwest::StatusCode::BAD_REQUEST,
-                "The maximum number of addresses is 50",
+            return Err(TransportError::InvalidArgument(
+                "The maximum number of addresses is 50".to_string(),
             ));
         }
+        let addresses = addresses.join(",");
         let mut params = vec![
             ("chainShortName", CHAIN_SHORT_NAME),
-            ("addresses", &addresses.join(",")),
+            ("addresses", addresses.as_str()),
         ];
         if let Some(protocol_type) = protocol_type {
             params.push(("protocolType", protocol_type));
@@ -348,16 +1219,16 @@ impl Oklink {
         is_from_or_to: Option<&str>,
         page: Option<&str>,
         limit: Option<&str>,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<BatchNormalTransactionList, TransportError> {
         if addresses.len() > 50 {
-            return Err(reqwest::Error::new(
-                reqwest::StatusCode::BAD_REQUEST,
-                "The maximum number of addresses is 50",
+            return Err(TransportError::InvalidArgument(
+                "The maximum number of addresses is 50".to_string(),
             ));
         }
+        let addresses = addresses.join(",");
         let mut params = vec![
             ("chainShortName", CHAIN_SHORT_NAME),
-            ("addresses", &addresses.join(",")),
+            ("addresses", addresses.as_str()),
         ];
         if let Some(start_block_height) = start_block_height {
             params.push(("startBlockHeight", start_block_height));
@@ -385,16 +1256,16 @@ impl Oklink {
         is_from_or_to: Option<&str>,
         page: Option<&str>,
         limit: Option<&str>,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<BatchInternalTransactionList, TransportError> {
         if addresses.len() > 20 {
-            return Err(reqwest::Error::new(
-                reqwest::StatusCode::BAD_REQUEST,
-                "The maximum number of addresses is 20",
+            return Err(TransportError::InvalidArgument(
+                "The maximum number of addresses is 20".to_string(),
             ));
         }
+        let addresses = addresses.join(",");
         let mut params = vec![
             ("chainShortName", CHAIN_SHORT_NAME),
-            ("addresses", &addresses.join(",")),
+            ("addresses", addresses.as_str()),
         ];
         if let Some(start_block_height) = start_block_height {
             params.push(("startBlockHeight", start_block_height));
@@ -411,23 +1282,10 @@ impl Oklink {
         if let Some(limit) = limit {
             params.push(("limit", limit));
         }
-        let url = format!(
-            "{}api/v5/explorer/address/internal-transaction-list-multi",
-            BASE_URL
-        );
-        let response = self
-            .client
-            .get(&url)
-            .headers(self.headers().into())
-            .query(&params)
-            .send()
-            .await?
-            .json::<AddressInformation>()
-            .await?;
-        Ok(response)
         self._get("api/v5/explorer/address/internal-transaction-list-multi", &params).await
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn batch_address_token_transaction_list(
         &self,
         addresses: &[&str],
@@ -438,16 +1296,16 @@ impl Oklink {
         protocol_type: Option<&str>,
         token_contract_address: Option<&str>,
         is_from_or_to: Option<&str>,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<BatchTokenTransactionList, TransportError> {
         if addresses.len() > 20 {
-            return Err(reqwest::Error::new(
-                reqwest::StatusCode::BAD_REQUEST,
-                "The maximum number of addresses is 20",
+            return Err(TransportError::InvalidArgument(
+                "The maximum number of addresses is 20".to_string(),
             ));
         }
+        let addresses = addresses.join(",");
         let mut params = vec![
             ("chainShortName", CHAIN_SHORT_NAME),
-            ("addresses", &addresses.join(",")),
+            ("addresses", addresses.as_str()),
             ("startBlockHeight", start_block_height),
             ("endBlockHeight", end_block_height),
         ];
@@ -472,7 +1330,7 @@ impl Oklink {
     pub async fn rich_list(
         &self,
         address: Option<&str>,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<RichList, TransportError> {
         let mut params = vec![("chainShortName", CHAIN_SHORT_NAME)];
         if let Some(address) = address {
             params.push(("address", address));
@@ -484,7 +1342,7 @@ impl Oklink {
         &self,
         page: Option<&str>,
         limit: Option<&str>,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<NativeTokenRanking, TransportError> {
         let mut params = vec![("chainShortName", CHAIN_SHORT_NAME)];
         if let Some(page) = page {
             params.push(("page", page));
@@ -501,7 +1359,7 @@ impl Oklink {
         height: Option<&str>,
         page: Option<&str>,
         limit: Option<&str>,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<BlockTransactionList, TransportError> {
         let mut params = vec![("chainShortName", CHAIN_SHORT_NAME)];
         if let Some(block_hash) = block_hash {
             params.push(("blockHash", block_hash));
@@ -524,7 +1382,7 @@ impl Oklink {
         height: Option<&str>,
         page: Option<&str>,
         limit: Option<&str>,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<LargeTransactionList, TransportError> {
         let mut params = vec![("chainShortName", CHAIN_SHORT_NAME)];
         if let Some(transaction_type) = transaction_type {
             params.push(("type", transaction_type));
@@ -545,7 +1403,7 @@ impl Oklink {
         &self,
         page: Option<&str>,
         limit: Option<&str>,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<AddressInformation, TransportError> {
         let mut params = vec![("chainShortName", CHAIN_SHORT_NAME)];
         if let Some(page) = page {
             params.push(("page", page));
@@ -561,7 +1419,7 @@ impl Oklink {
         tx_id: &str,
         page: Option<&str>,
         limit: Option<&str>,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<AddressInformation, TransportError> {
         let mut params = vec![("chainShortName", CHAIN_SHORT_NAME), ("txId", tx_id)];
         if let Some(page) = page {
             params.push(("page", page));
@@ -578,11 +1436,9 @@ impl Oklink {
         protocol_type: &str,
         page: Option<&str>,
         limit: Option<&str>,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<AddressInformation, TransportError> {
         let mut params = vec![("chainShortName", CHAIN_SHORT_NAME), ("txId", tx_id)];
-        if let Some(protocol_type) = protocol_type {
-            params.push(("protocolType", protocol_type));
-        }
+        params.push(("protocolType", protocol_type));
         if let Some(page) = page {
             params.push(("page", page));
         }
@@ -595,7 +1451,7 @@ impl Oklink {
     pub async fn transaction_details(
         &self,
         tx_id: &str,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<TransactionDetails, TransportError> {
         let params = [("chainShortName", CHAIN_SHORT_NAME), ("txId", tx_id)];
         self._get("api/v5/explorer/transaction/transaction-fills", &params).await
     }
@@ -603,16 +1459,16 @@ impl Oklink {
     pub async fn batch_transaction_details(
         &self,
         tx_ids: &[&str],
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<BatchTransactionDetails, TransportError> {
         if tx_ids.len() > 20 {
-            return Err(reqwest::Error::new(
-                reqwest::StatusCode::BAD_REQUEST,
-                "The maximum number of transactions is 20",
+            return Err(TransportError::InvalidArgument(
+                "The maximum number of transactions is 20".to_string(),
             ));
         }
+        let tx_ids = tx_ids.join(",");
         let params = [
             ("chainShortName", CHAIN_SHORT_NAME),
-            ("txIds", &tx_ids.join(",")),
+            ("txIds", tx_ids.as_str()),
         ];
         self._get("api/v5/explorer/transaction/transaction-multi", &params).await
     }
@@ -620,16 +1476,16 @@ impl Oklink {
     pub async fn batch_internal_transaction_details(
         &self,
         tx_ids: &[&str],
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<AddressInformation, TransportError> {
         if tx_ids.len() > 20 {
-            return Err(reqwest::Error::new(
-                reqwest::StatusCode::BAD_REQUEST,
-                "The maximum number of transactions is 20",
+            return Err(TransportError::InvalidArgument(
+                "The maximum number of transactions is 20".to_string(),
             ));
         }
+        let tx_ids = tx_ids.join(",");
         let params = [
             ("chainShortName", CHAIN_SHORT_NAME),
-            ("txIds", &tx_ids.join(",")),
+            ("txIds", tx_ids.as_str()),
         ];
         self._get("api/v5/explorer/transaction/internal-transaction-multi", &params).await
     }
@@ -640,16 +1496,16 @@ impl Oklink {
         protocol_type: Option<&str>,
         page: Option<&str>,
         limit: Option<&str>,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<AddressInformation, TransportError> {
         if tx_ids.len() > 20 {
-            return Err(reqwest::Error::new(
-                reqwest::StatusCode::BAD_REQUEST,
-                "The maximum number of transactions is 20",
+            return Err(TransportError::InvalidArgument(
+                "The maximum number of transactions is 20".to_string(),
             ));
         }
+        let tx_ids = tx_ids.join(",");
         let mut params = vec![
             ("chainShortName", CHAIN_SHORT_NAME),
-            ("txIds", &tx_ids.join(",")),
+            ("txIds", tx_ids.as_str()),
         ];
         if let Some(protocol_type) = protocol_type {
             params.push(("protocolType", protocol_type));
@@ -663,6 +1519,7 @@ impl Oklink {
         self._get("api/v5/explorer/transaction/token-transfer-multi", &params).await
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn token_list(
         &self,
         protocol_type: Option<&str>,
@@ -672,7 +1529,7 @@ impl Oklink {
         order_by: Option<&str>,
         page: Option<&str>,
         limit: Option<&str>,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<TokenList, TransportError> {
         let mut params = vec![("chainShortName", CHAIN_SHORT_NAME)];
         if let Some(protocol_type) = protocol_type {
             params.push(("protocolType", protocol_type));
@@ -704,7 +1561,7 @@ impl Oklink {
         holder_address: Option<&str>,
         page: Option<&str>,
         limit: Option<&str>,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<TokenPositionList, TransportError> {
         let mut params = vec![
             ("chainShortName", CHAIN_SHORT_NAME),
             ("tokenContractAddress", token_contract_address),
@@ -727,7 +1584,7 @@ impl Oklink {
         holder_address: Option<&str>,
         page: Option<&str>,
         limit: Option<&str>,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<TokenPositionList, TransportError> {
         let mut params = vec![
             ("chainShortName", CHAIN_SHORT_NAME),
             ("tokenContractAddress", token_contract_address),
@@ -751,7 +1608,7 @@ impl Oklink {
         min_amount: Option<&str>,
         page: Option<&str>,
         limit: Option<&str>,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<TokenTransferList, TransportError> {
         let mut params = vec![
             ("chainShortName", CHAIN_SHORT_NAME),
             ("tokenContractAddress", token_contract_address),
@@ -778,7 +1635,7 @@ impl Oklink {
         end_block_height: &str,
         page: Option<&str>,
         limit: Option<&str>,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<AddressInformation, TransportError> {
         let mut params = vec![
             ("chainShortName", CHAIN_SHORT_NAME),
             ("tokenContractAddress", token_contract_address),
@@ -798,7 +1655,7 @@ impl Oklink {
         &self,
         token_contract_address: &str,
         height: &str,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<TokenSupplyHistory, TransportError> {
         let params = [
             ("chainShortName", CHAIN_SHORT_NAME),
             ("tokenContractAddress", token_contract_address),
@@ -813,7 +1670,7 @@ impl Oklink {
         order_by: Option<&str>,
         page: Option<&str>,
         limit: Option<&str>,
-    ) -> Result<AddressInformation, reqwest::Error> {
+    ) -> Result<AddressInformation, TransportError> {
         let mut params = vec![
             ("chainShortName", CHAIN_SHORT_NAME),
             ("tokenContractAddress", token_contract_address),
@@ -829,4 +1686,90 @@ impl Oklink {
         }
         self._get("api/v5/explorer/token/transaction-stats", &params).await
     }
+
+    pub async fn nft_transaction_list(
+        &self,
+        token_contract_address: &str,
+        token_id: Option<&str>,
+        page: Option<&str>,
+        limit: Option<&str>,
+    ) -> Result<NftTransactionList, TransportError> {
+        let mut params = vec![
+            ("chainShortName", CHAIN_SHORT_NAME),
+            ("tokenContractAddress", token_contract_address),
+        ];
+        if let Some(token_id) = token_id {
+            params.push(("tokenId", token_id));
+        }
+        if let Some(page) = page {
+            params.push(("page", page));
+        }
+        if let Some(limit) = limit {
+            params.push(("limit", limit));
+        }
+        self._get("api/v5/explorer/nft/transaction-list", &params).await
+    }
+
+    pub async fn nft_collection_info(
+        &self,
+        token_contract_address: &str,
+    ) -> Result<NftCollectionInfo, TransportError> {
+        let params = [
+            ("chainShortName", CHAIN_SHORT_NAME),
+            ("tokenContractAddress", token_contract_address),
+        ];
+        self._get("api/v5/explorer/nft/collection-info", &params).await
+    }
+
+    pub async fn nft_details(
+        &self,
+        token_contract_address: &str,
+        token_id: &str,
+    ) -> Result<NftDetails, TransportError> {
+        let params = [
+            ("chainShortName", CHAIN_SHORT_NAME),
+            ("tokenContractAddress", token_contract_address),
+            ("tokenId", token_id),
+        ];
+        self._get("api/v5/explorer/nft/nft-details", &params).await
+    }
+
+    pub async fn token_price(
+        &self,
+        token_contract_addresses: &[&str],
+    ) -> Result<TokenPriceList, TransportError> {
+        let addresses = token_contract_addresses.join(",");
+        let params = [
+            ("chainId", CHAIN_ID),
+            ("tokenContractAddress", addresses.as_str()),
+        ];
+        self._get("api/v5/explorer/tokenprice/price-multi", &params).await
+    }
+
+    pub async fn token_price_history(
+        &self,
+        token_contract_address: &str,
+        period: Option<&str>,
+        after: Option<&str>,
+        before: Option<&str>,
+        limit: Option<&str>,
+    ) -> Result<TokenPriceHistory, TransportError> {
+        let mut params = vec![
+            ("chainId", CHAIN_ID),
+            ("tokenContractAddress", token_contract_address),
+        ];
+        if let Some(period) = period {
+            params.push(("period", period));
+        }
+        if let Some(after) = after {
+            params.push(("after", after));
+        }
+        if let Some(before) = before {
+            params.push(("before", before));
+        }
+        if let Some(limit) = limit {
+            params.push(("limit", limit));
+        }
+        self._get("api/v5/explorer/tokenprice/historical", &params).await
+    }
 }
\ No newline at end of file
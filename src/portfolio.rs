@@ -0,0 +1,459 @@
+use futures::stream::{self, StreamExt, TryStreamExt};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::amount::{AmountError, TokenMeta};
+use crate::oklink::{AddressTokenHolding, Oklink, TokenBalance};
+use crate::transport::TransportError;
+use crate::types::ProtocolType;
+use crate::valuation::price_token;
+
+const PAGE_LIMIT: &str = "50";
+const BATCH_BALANCE_LIMIT: usize = 50;
+// Upper bound on per-token requests in flight at once.
+const TOKEN_CONCURRENCY: usize = 8;
+
+#[derive(Debug, Clone)]
+pub struct Portfolio {
+    pub address: String,
+    pub native: NativeBalance,
+    pub fungible: Vec<FungibleBalance>,
+    pub nfts: Vec<NftCollection>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NativeBalance {
+    pub symbol: String,
+    pub balance: String,
+    // Priced through wrapped KAIA; unset for historical balances.
+    pub price_usd: Option<f64>,
+    pub value_usd: Option<f64>,
+}
+
+impl NativeBalance {
+    fn new(symbol: String, balance: String, price_usd: Option<f64>) -> Self {
+        let value_usd = price_usd
+            .zip(parse_usd(&balance))
+            .map(|(price, balance)| price * balance);
+        NativeBalance {
+            symbol,
+            balance,
+            price_usd,
+            value_usd,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FungibleBalance {
+    pub token_contract_address: String,
+    pub symbol: String,
+    pub decimals: Option<u32>,
+    pub amount: String,
+    pub price_usd: Option<f64>,
+    pub value_usd: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NftCollection {
+    pub token_contract_address: String,
+    pub symbol: String,
+    pub protocol_type: ProtocolType,
+    pub token_ids: Vec<String>,
+    // Number of tokens held; for KIP-37 this sums the amounts of every token ID.
+    pub count: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct HistoricalPortfolio {
+    pub address: String,
+    pub height: String,
+    pub block_time: String,
+    pub native: NativeBalance,
+    pub tokens: Vec<HistoricalTokenBalance>,
+}
+
+#[derive(Debug, Clone)]
+pub struct HistoricalTokenBalance {
+    pub token_contract_address: String,
+    pub symbol: String,
+    pub balance: String,
+    pub total_supply: Option<String>,
+    pub share_of_supply: Option<f64>,
+}
+
+impl Portfolio {
+    pub fn total_value_usd(&self) -> f64 {
+        self.native.value_usd.unwrap_or(0.0)
+            + self
+                .fungible
+                .iter()
+                .filter_map(|b| b.value_usd)
+                .sum::<f64>()
+    }
+}
+
+fn parse_usd(value: &str) -> Option<f64> {
+    value.parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
+fn group_nfts(
+    protocol_type: ProtocolType,
+    balances: &[(String, String, String, String)],
+) -> Vec<NftCollection> {
+    let mut collections: BTreeMap<String, NftCollection> = BTreeMap::new();
+    for (token_contract_address, symbol, token_id, amount) in balances {
+        let collection = collections
+            .entry(token_contract_address.clone())
+            .or_insert_with(|| NftCollection {
+                token_contract_address: token_contract_address.clone(),
+                symbol: symbol.clone(),
+                protocol_type,
+                token_ids: Vec::new(),
+                count: 0,
+            });
+        if !token_id.is_empty() {
+            collection.token_ids.push(token_id.clone());
+        }
+        collection.count += match protocol_type {
+            ProtocolType::Token1155 => amount.parse::<u64>().unwrap_or(1),
+            _ => 1,
+        };
+    }
+    collections.into_values().collect()
+}
+
+impl Oklink {
+    pub async fn portfolio(&self, address: &str) -> Result<Portfolio, TransportError> {
+        let (info, fungible, nft_721, nft_1155, native_prices) = futures::try_join!(
+            self.address_info(address),
+            self.all_token_balances(address, ProtocolType::Token20),
+            self.all_token_balances(address, ProtocolType::Token721),
+            self.all_token_balances(address, ProtocolType::Token1155),
+            self.usd_prices([""]),
+        )?;
+
+        let token_metas = self
+            .token_metas(fungible.iter().map(|b| b.token_contract_address.as_str()))
+            .await?;
+        let fungible = fungible
+            .into_iter()
+            .map(|balance| {
+                let meta = token_metas.get(&balance.token_contract_address);
+                FungibleBalance {
+                    decimals: meta.map(|meta| meta.decimals),
+                    price_usd: parse_usd(&balance.price_usd),
+                    value_usd: parse_usd(&balance.value_usd),
+                    token_contract_address: balance.token_contract_address,
+                    symbol: balance.symbol,
+                    amount: balance.holding_amount,
+                }
+            })
+            .collect();
+
+        let nft_rows = |balances: Vec<TokenBalance>| -> Vec<(String, String, String, String)> {
+            balances
+                .into_iter()
+                .map(|b| {
+                    (
+                        b.token_contract_address,
+                        b.symbol,
+                        b.token_id,
+                        b.holding_amount,
+                    )
+                })
+                .collect()
+        };
+        let mut nfts = group_nfts(ProtocolType::Token721, &nft_rows(nft_721));
+        nfts.extend(group_nfts(ProtocolType::Token1155, &nft_rows(nft_1155)));
+
+        Ok(Portfolio {
+            address: address.to_string(),
+            native: NativeBalance::new(
+                info.data.balance_symbol,
+                info.data.balance,
+                native_prices.get(&price_token("")).copied(),
+            ),
+            fungible,
+            nfts,
+        })
+    }
+
+    pub async fn portfolios(&self, addresses: &[&str]) -> Result<Vec<Portfolio>, TransportError> {
+        let mut portfolios: Vec<Portfolio> = Vec::with_capacity(addresses.len());
+        for chunk in addresses.chunks(BATCH_BALANCE_LIMIT) {
+            let (natives, fungible, nft_721, nft_1155) = futures::try_join!(
+                self.batch_address_balances(chunk),
+                self.all_batch_token_balances(chunk, ProtocolType::Token20),
+                self.all_batch_token_balances(chunk, ProtocolType::Token721),
+                self.all_batch_token_balances(chunk, ProtocolType::Token1155),
+            )?;
+
+            let (token_metas, prices) = futures::try_join!(
+                self.token_metas(
+                    fungible
+                        .iter()
+                        .chain(nft_721.iter())
+                        .chain(nft_1155.iter())
+                        .map(|h| h.token_contract_address.as_str()),
+                ),
+                self.usd_prices(
+                    fungible
+                        .iter()
+                        .map(|h| h.token_contract_address.as_str())
+                        .chain([""]),
+                ),
+            )?;
+            let symbol_of = |token: &str| {
+                token_metas
+                    .get(token)
+                    .map(|meta| meta.symbol.clone())
+                    .unwrap_or_default()
+            };
+
+            for address in chunk {
+                let balance = natives
+                    .data
+                    .balance_list
+                    .iter()
+                    .find(|b| b.address.eq_ignore_ascii_case(address))
+                    .map(|b| b.balance.clone())
+                    .unwrap_or_else(|| "0".to_string());
+                let fungible = fungible
+                    .iter()
+                    .filter(|h| h.address.eq_ignore_ascii_case(address))
+                    .map(|h| {
+                        let price_usd =
+                            prices.get(&price_token(&h.token_contract_address)).copied();
+                        let value_usd = price_usd
+                            .zip(parse_usd(&h.holding_amount))
+                            .map(|(price, amount)| price * amount);
+                        FungibleBalance {
+                            token_contract_address: h.token_contract_address.clone(),
+                            symbol: symbol_of(&h.token_contract_address),
+                            decimals: token_metas
+                                .get(&h.token_contract_address)
+                                .map(|meta| meta.decimals),
+                            amount: h.holding_amount.clone(),
+                            price_usd,
+                            value_usd,
+                        }
+                    })
+                    .collect();
+                let nft_rows = |holdings: &[AddressTokenHolding]| {
+                    holdings
+                        .iter()
+                        .filter(|h| h.address.eq_ignore_ascii_case(address))
+                        .map(|h| {
+                            (
+                                h.token_contract_address.clone(),
+                                symbol_of(&h.token_contract_address),
+                                h.token_id.clone(),
+                                h.holding_amount.clone(),
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                };
+                let mut nfts = group_nfts(ProtocolType::Token721, &nft_rows(&nft_721));
+                nfts.extend(group_nfts(ProtocolType::Token1155, &nft_rows(&nft_1155)));
+
+                portfolios.push(Portfolio {
+                    address: address.to_string(),
+                    native: NativeBalance::new(
+                        natives.data.symbol.clone(),
+                        balance,
+                        prices.get(&price_token("")).copied(),
+                    ),
+                    fungible,
+                    nfts,
+                });
+            }
+        }
+        Ok(portfolios)
+    }
+
+    // Tokens are every KIP-7 token the address sent or received up to
+    // `height`, so tokens sold since then are included.
+    pub async fn portfolio_at(
+        &self,
+        address: &str,
+        height: &str,
+    ) -> Result<HistoricalPortfolio, TransportError> {
+        let tokens = self.tokens_transferred_until(address, height).await?;
+        let tokens: Vec<&str> = tokens.iter().map(String::as_str).collect();
+        self.portfolio_at_with_tokens(address, height, &tokens)
+            .await
+    }
+
+    pub async fn portfolio_at_with_tokens(
+        &self,
+        address: &str,
+        height: &str,
+        token_contract_addresses: &[&str],
+    ) -> Result<HistoricalPortfolio, TransportError> {
+        let mut tokens = token_contract_addresses.to_vec();
+        tokens.sort_unstable();
+        tokens.dedup();
+
+        let token_balances = stream::iter(tokens)
+            .map(|token| async move {
+                let (balance, supply) = futures::try_join!(
+                    self.address_balance_history(address, height, Some(token)),
+                    self.token_supply_history(token, height),
+                )?;
+                Ok::<_, TransportError>((token, balance, supply))
+            })
+            .buffered(TOKEN_CONCURRENCY)
+            .try_collect::<Vec<_>>();
+        let (native, token_balances) = futures::try_join!(
+            self.address_balance_history(address, height, None),
+            token_balances,
+        )?;
+
+        let tokens = token_balances
+            .into_iter()
+            .map(|(token, balance, supply)| {
+                let total_supply = Some(supply.data.total_supply).filter(|s| !s.is_empty());
+                let share_of_supply = total_supply
+                    .as_deref()
+                    .and_then(|s| s.parse::<f64>().ok())
+                    .filter(|s| *s > 0.0)
+                    .zip(balance.data.balance.parse::<f64>().ok())
+                    .map(|(supply, balance)| balance / supply);
+                HistoricalTokenBalance {
+                    token_contract_address: token.to_string(),
+                    symbol: balance.data.balance_symbol,
+                    balance: balance.data.balance,
+                    total_supply,
+                    share_of_supply,
+                }
+            })
+            .collect();
+
+        Ok(HistoricalPortfolio {
+            address: address.to_string(),
+            height: native.data.height,
+            block_time: native.data.block_time,
+            native: NativeBalance::new(native.data.balance_symbol, native.data.balance, None),
+            tokens,
+        })
+    }
+
+    // Distinct KIP-7 tokens in the address' transfers at or below `height`.
+    async fn tokens_transferred_until(
+        &self,
+        address: &str,
+        height: &str,
+    ) -> Result<Vec<String>, TransportError> {
+        let max_height = height.parse::<u64>().ok();
+        let mut tokens: Vec<String> = Vec::new();
+        let mut page = 1u32;
+        loop {
+            let page_str = page.to_string();
+            let response = self
+                .address_token_transaction_list_by_height(
+                    address,
+                    ProtocolType::Token20.as_str(),
+                    None,
+                    None,
+                    Some(height),
+                    Some(&page_str),
+                    Some(PAGE_LIMIT),
+                )
+                .await?;
+            let total_page = response.data.total_page.parse::<u32>().unwrap_or(1);
+            tokens.extend(
+                response
+                    .data
+                    .transaction_lists
+                    .into_iter()
+                    .filter(|tx| {
+                        max_height.is_none() || tx.height.parse::<u64>().ok() <= max_height
+                    })
+                    .map(|tx| tx.token_contract_address.to_lowercase()),
+            );
+            if page >= total_page {
+                break;
+            }
+            page += 1;
+        }
+        tokens.sort_unstable();
+        tokens.dedup();
+        Ok(tokens)
+    }
+
+    async fn all_token_balances(
+        &self,
+        address: &str,
+        protocol_type: ProtocolType,
+    ) -> Result<Vec<TokenBalance>, TransportError> {
+        let mut balances = Vec::new();
+        let mut page = 1u32;
+        loop {
+            let page_str = page.to_string();
+            let response = self
+                .address_token_balance(
+                    address,
+                    protocol_type,
+                    None,
+                    Some(&page_str),
+                    Some(PAGE_LIMIT),
+                )
+                .await?;
+            let total_page = response.data.total_page.parse::<u32>().unwrap_or(1);
+            balances.extend(response.data.token_list);
+            if page >= total_page {
+                return Ok(balances);
+            }
+            page += 1;
+        }
+    }
+
+    async fn all_batch_token_balances(
+        &self,
+        addresses: &[&str],
+        protocol_type: ProtocolType,
+    ) -> Result<Vec<AddressTokenHolding>, TransportError> {
+        let mut holdings = Vec::new();
+        let mut page = 1u32;
+        loop {
+            let page_str = page.to_string();
+            let response = self
+                .batch_address_token_balances(
+                    addresses,
+                    Some(protocol_type.as_str()),
+                    Some(&page_str),
+                    Some(PAGE_LIMIT),
+                )
+                .await?;
+            let total_page = response.data.total_page.parse::<u32>().unwrap_or(1);
+            holdings.extend(response.data.balance_list);
+            if page >= total_page {
+                return Ok(holdings);
+            }
+            page += 1;
+        }
+    }
+
+    // Decimals and symbols through the shared `token_meta` cache. Tokens the
+    // API doesn't know are left out.
+    async fn token_metas<'a>(
+        &self,
+        token_contract_addresses: impl Iterator<Item = &'a str>,
+    ) -> Result<HashMap<String, TokenMeta>, TransportError> {
+        let mut unique: Vec<&str> = token_contract_addresses.collect();
+        unique.sort_unstable();
+        unique.dedup();
+        stream::iter(unique)
+            .map(|token| async move {
+                match self.token_meta(token).await {
+                    Ok(meta) => Ok(Some((token.to_string(), meta))),
+                    Err(AmountError::Http(e)) => Err(e),
+                    Err(_) => Ok(None),
+                }
+            })
+            .buffer_unordered(TOKEN_CONCURRENCY)
+            .try_filter_map(|entry| async move { Ok(entry) })
+            .try_collect()
+            .await
+    }
+}
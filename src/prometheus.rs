@@ -0,0 +1,185 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::middleware::{api_code, endpoint_of, Middleware};
+use crate::transport::{HttpRequest, HttpResponse, TransportError};
+
+// Upper bounds of the latency and key wait histogram buckets, in seconds.
+const LATENCY_BUCKETS: [f64; 10] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+#[derive(Debug, Default)]
+struct Histogram {
+    // Cumulative counts, one per bucket in `LATENCY_BUCKETS`.
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += seconds;
+    }
+}
+
+#[derive(Debug, Default)]
+struct Registry {
+    requests: BTreeMap<String, u64>,
+    // Keyed by endpoint and error code. The code is the OKLink `code` field,
+    // "http_<status>" for error statuses without one, or "transport".
+    errors: BTreeMap<(String, String), u64>,
+    latency: BTreeMap<String, Histogram>,
+    // Time spent waiting for a usable key before the request was sent.
+    key_wait: BTreeMap<String, Histogram>,
+}
+
+// Middleware collecting request counts, errors by code, and latency and key
+// wait histograms per endpoint, rendered in the Prometheus text format. Clones
+// share the same registry.
+#[derive(Debug, Clone, Default)]
+pub struct PrometheusMetrics {
+    registry: Arc<Mutex<Registry>>,
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn write_histogram(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    histograms: &BTreeMap<String, Histogram>,
+) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} histogram", name);
+    for (endpoint, histogram) in histograms {
+        let endpoint = escape_label(endpoint);
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets) {
+            let _ = writeln!(
+                out,
+                "{}_bucket{{endpoint=\"{}\",le=\"{}\"}} {}",
+                name, endpoint, bound, count
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{}_bucket{{endpoint=\"{}\",le=\"+Inf\"}} {}",
+            name, endpoint, histogram.count
+        );
+        let _ = writeln!(
+            out,
+            "{}_sum{{endpoint=\"{}\"}} {}",
+            name, endpoint, histogram.sum
+        );
+        let _ = writeln!(
+            out,
+            "{}_count{{endpoint=\"{}\"}} {}",
+            name, endpoint, histogram.count
+        );
+    }
+}
+
+impl PrometheusMetrics {
+    pub fn new() -> Self {
+        PrometheusMetrics::default()
+    }
+
+    fn record(&self, request: &HttpRequest, elapsed: Duration, error_code: Option<String>) {
+        let endpoint = endpoint_of(&request.url).to_string();
+        let mut registry = self.registry.lock().unwrap();
+        *registry.requests.entry(endpoint.clone()).or_default() += 1;
+        if let Some(code) = error_code {
+            *registry.errors.entry((endpoint.clone(), code)).or_default() += 1;
+        }
+        registry
+            .latency
+            .entry(endpoint)
+            .or_default()
+            .observe(elapsed.as_secs_f64());
+    }
+
+    pub fn render(&self) -> String {
+        let registry = self.registry.lock().unwrap();
+        let mut out = String::new();
+
+        out.push_str("# HELP oklink_requests_total OKLink API requests sent.\n");
+        out.push_str("# TYPE oklink_requests_total counter\n");
+        for (endpoint, count) in &registry.requests {
+            let _ = writeln!(
+                out,
+                "oklink_requests_total{{endpoint=\"{}\"}} {}",
+                escape_label(endpoint),
+                count
+            );
+        }
+
+        out.push_str("# HELP oklink_errors_total OKLink API requests that failed, by code.\n");
+        out.push_str("# TYPE oklink_errors_total counter\n");
+        for ((endpoint, code), count) in &registry.errors {
+            let _ = writeln!(
+                out,
+                "oklink_errors_total{{endpoint=\"{}\",code=\"{}\"}} {}",
+                escape_label(endpoint),
+                escape_label(code),
+                count
+            );
+        }
+
+        write_histogram(
+            &mut out,
+            "oklink_request_duration_seconds",
+            "OKLink API request latency.",
+            &registry.latency,
+        );
+        write_histogram(
+            &mut out,
+            "oklink_rate_limit_wait_seconds",
+            "Time OKLink API requests waited for a key that was not disabled or rate limited.",
+            &registry.key_wait,
+        );
+        out
+    }
+}
+
+impl Middleware for PrometheusMetrics {
+    fn after_response(
+        &self,
+        request: &HttpRequest,
+        response: &mut HttpResponse,
+        elapsed: Duration,
+    ) {
+        let error_code = match api_code(&response.body) {
+            Some(code) if code != "0" => Some(code),
+            Some(_) => None,
+            None if response.status >= 400 => Some(format!("http_{}", response.status)),
+            None => None,
+        };
+        self.record(request, elapsed, error_code);
+    }
+
+    fn on_error(&self, request: &HttpRequest, _error: &TransportError, elapsed: Duration) {
+        self.record(request, elapsed, Some("transport".to_string()));
+    }
+
+    fn on_key_wait(&self, url: &str, waited: Duration) {
+        let endpoint = endpoint_of(url).to_string();
+        self.registry
+            .lock()
+            .unwrap()
+            .key_wait
+            .entry(endpoint)
+            .or_default()
+            .observe(waited.as_secs_f64());
+    }
+}
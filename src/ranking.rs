@@ -0,0 +1,269 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::amount::{AmountError, TokenAmount};
+use crate::clock::unix_time_ms;
+use crate::oklink::{Oklink, RichListEntry};
+use crate::transport::TransportError;
+
+const PAGE_LIMIT: &str = "100";
+// Both rankings list native KAIA balances.
+const NATIVE_DECIMALS: u32 = 18;
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Http(TransportError),
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Amount(AmountError),
+    // The snapshots rank different things and can't be compared.
+    Mismatch {
+        from: (RankingKind, String),
+        to: (RankingKind, String),
+    },
+}
+
+impl From<AmountError> for SnapshotError {
+    fn from(e: AmountError) -> Self {
+        SnapshotError::Amount(e)
+    }
+}
+
+impl From<TransportError> for SnapshotError {
+    fn from(e: TransportError) -> Self {
+        SnapshotError::Http(e)
+    }
+}
+
+impl From<std::io::Error> for SnapshotError {
+    fn from(e: std::io::Error) -> Self {
+        SnapshotError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for SnapshotError {
+    fn from(e: serde_json::Error) -> Self {
+        SnapshotError::Json(e)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RankingKind {
+    RichList,
+    NativeTokenRanking,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RankingEntry {
+    pub rank: u32,
+    pub address: String,
+    // Exact decimal balance as returned by the API.
+    pub amount: String,
+}
+
+impl RankingEntry {
+    pub fn token_amount(&self) -> Result<TokenAmount, AmountError> {
+        TokenAmount::from_units(&self.amount, NATIVE_DECIMALS)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RankingSnapshot {
+    pub kind: RankingKind,
+    pub symbol: String,
+    // Milliseconds since the Unix epoch.
+    pub taken_at: u64,
+    pub entries: Vec<RankingEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RankChange {
+    pub address: String,
+    pub old_rank: u32,
+    pub new_rank: u32,
+    pub old_amount: String,
+    pub new_amount: String,
+    // Exact signed decimal, e.g. "-1.5".
+    pub amount_delta: String,
+}
+
+impl RankChange {
+    // Positive when the address moved up the ranking.
+    pub fn rank_delta(&self) -> i64 {
+        self.old_rank as i64 - self.new_rank as i64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RankingDiff {
+    pub from_taken_at: u64,
+    pub to_taken_at: u64,
+    pub entrants: Vec<RankingEntry>,
+    pub exits: Vec<RankingEntry>,
+    // Addresses present in both snapshots whose rank or balance changed,
+    // ordered by their new rank.
+    pub changes: Vec<RankChange>,
+}
+
+impl RankingDiff {
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+fn entries(list: Vec<RichListEntry>, offset: usize) -> Vec<RankingEntry> {
+    list.into_iter()
+        .enumerate()
+        .map(|(i, entry)| RankingEntry {
+            rank: entry.rank.parse().unwrap_or((offset + i + 1) as u32),
+            address: entry.holder_address,
+            amount: entry.amount,
+        })
+        .collect()
+}
+
+// `new - old` as a signed decimal string.
+fn signed_delta(old: &TokenAmount, new: &TokenAmount) -> String {
+    match new.checked_sub(old) {
+        Some(gain) => gain.to_units_string(),
+        None => old
+            .checked_sub(new)
+            .map(|loss| format!("-{}", loss.to_units_string()))
+            .unwrap_or_default(),
+    }
+}
+
+impl RankingSnapshot {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SnapshotError> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SnapshotError> {
+        if let Some(dir) = path.as_ref().parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    // Compares this snapshot against a newer one of the same kind and symbol.
+    // Addresses are matched case-insensitively.
+    pub fn diff(&self, newer: &RankingSnapshot) -> Result<RankingDiff, SnapshotError> {
+        if self.kind != newer.kind || !self.symbol.eq_ignore_ascii_case(&newer.symbol) {
+            return Err(SnapshotError::Mismatch {
+                from: (self.kind, self.symbol.clone()),
+                to: (newer.kind, newer.symbol.clone()),
+            });
+        }
+        let old: HashMap<String, &RankingEntry> = self
+            .entries
+            .iter()
+            .map(|e| (e.address.to_lowercase(), e))
+            .collect();
+        let new: HashMap<String, &RankingEntry> = newer
+            .entries
+            .iter()
+            .map(|e| (e.address.to_lowercase(), e))
+            .collect();
+
+        let entrants = newer
+            .entries
+            .iter()
+            .filter(|e| !old.contains_key(&e.address.to_lowercase()))
+            .cloned()
+            .collect();
+        let exits = self
+            .entries
+            .iter()
+            .filter(|e| !new.contains_key(&e.address.to_lowercase()))
+            .cloned()
+            .collect();
+        let mut changes: Vec<RankChange> = Vec::new();
+        for current in &newer.entries {
+            let Some(previous) = old.get(&current.address.to_lowercase()) else {
+                continue;
+            };
+            let old_amount = previous.token_amount()?;
+            let new_amount = current.token_amount()?;
+            if previous.rank == current.rank && old_amount == new_amount {
+                continue;
+            }
+            changes.push(RankChange {
+                address: current.address.clone(),
+                old_rank: previous.rank,
+                new_rank: current.rank,
+                old_amount: previous.amount.clone(),
+                new_amount: current.amount.clone(),
+                amount_delta: signed_delta(&old_amount, &new_amount),
+            });
+        }
+        changes.sort_by_key(|c| c.new_rank);
+
+        Ok(RankingDiff {
+            from_taken_at: self.taken_at,
+            to_taken_at: newer.taken_at,
+            entrants,
+            exits,
+            changes,
+        })
+    }
+}
+
+impl Oklink {
+    // Captures the full ranking, walking every page of the native token
+    // ranking. The rich list endpoint is not paginated.
+    pub async fn ranking_snapshot(
+        &self,
+        kind: RankingKind,
+    ) -> Result<RankingSnapshot, TransportError> {
+        let taken_at = unix_time_ms();
+        let mut list: Vec<RankingEntry> = Vec::new();
+        let mut symbol = String::new();
+        match kind {
+            RankingKind::RichList => {
+                let response = self.rich_list(None).await?;
+                if let Some(first) = response.data.first() {
+                    symbol = first.symbol.clone();
+                }
+                list = entries(response.data, 0);
+            }
+            RankingKind::NativeTokenRanking => {
+                let mut page = 1u32;
+                loop {
+                    let page_str = page.to_string();
+                    let response = self
+                        .native_token_ranking(Some(&page_str), Some(PAGE_LIMIT))
+                        .await?;
+                    if let Some(first) = response.data.position_list.first() {
+                        symbol = first.symbol.clone();
+                    }
+                    let offset = list.len();
+                    list.extend(entries(response.data.position_list, offset));
+                    if page >= response.data.total_page.parse::<u32>().unwrap_or(1) {
+                        break;
+                    }
+                    page += 1;
+                }
+            }
+        }
+        Ok(RankingSnapshot {
+            kind,
+            symbol,
+            taken_at,
+            entries: list,
+        })
+    }
+
+    pub async fn save_ranking_snapshot(
+        &self,
+        kind: RankingKind,
+        path: impl AsRef<Path>,
+    ) -> Result<RankingSnapshot, SnapshotError> {
+        let snapshot = self.ranking_snapshot(kind).await?;
+        snapshot.save(path)?;
+        Ok(snapshot)
+    }
+}
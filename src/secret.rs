@@ -0,0 +1,93 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use zeroize::Zeroize;
+
+#[derive(Debug)]
+pub enum SecretError {
+    Env(std::env::VarError),
+    Io(std::io::Error),
+    Empty,
+    Provider(String),
+}
+
+impl From<std::env::VarError> for SecretError {
+    fn from(e: std::env::VarError) -> Self {
+        SecretError::Env(e)
+    }
+}
+
+impl From<std::io::Error> for SecretError {
+    fn from(e: std::io::Error) -> Self {
+        SecretError::Io(e)
+    }
+}
+
+// An OKLink API key. Debug and Display never print it, and the memory is
+// wiped when the key is dropped.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ApiKey(String);
+
+impl ApiKey {
+    pub fn new(key: impl Into<String>) -> Self {
+        ApiKey(key.into())
+    }
+
+    pub fn from_env(var: &str) -> Result<Self, SecretError> {
+        ApiKey::parse(std::env::var(var)?)
+    }
+
+    // Reads the key from a file such as a mounted secret, ignoring
+    // surrounding whitespace.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, SecretError> {
+        ApiKey::parse(fs::read_to_string(path)?)
+    }
+
+    fn parse(mut value: String) -> Result<Self, SecretError> {
+        let key = ApiKey::new(value.trim());
+        value.zeroize();
+        if key.0.is_empty() {
+            return Err(SecretError::Empty);
+        }
+        Ok(key)
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Drop for ApiKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ApiKey(***)")
+    }
+}
+
+impl fmt::Display for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("***")
+    }
+}
+
+impl From<String> for ApiKey {
+    fn from(key: String) -> Self {
+        ApiKey(key)
+    }
+}
+
+impl From<&str> for ApiKey {
+    fn from(key: &str) -> Self {
+        ApiKey::new(key)
+    }
+}
+
+// Called whenever a pooled key has to be (re)loaded, e.g. from a vault.
+pub type CredentialProvider = Arc<dyn Fn() -> Result<ApiKey, SecretError> + Send + Sync>;
@@ -0,0 +1,89 @@
+use futures::channel::oneshot;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::Mutex;
+
+use crate::transport::TransportError;
+
+type Waiter<T> = oneshot::Sender<Result<T, TransportError>>;
+
+// Coalesces concurrent calls with the same key: the first caller runs the
+// call and everyone who arrives while it is in flight gets a copy of its
+// result, errors included.
+pub(crate) struct SingleFlight<K, T> {
+    calls: Mutex<HashMap<K, Vec<Waiter<T>>>>,
+}
+
+impl<K, T> Default for SingleFlight<K, T> {
+    fn default() -> Self {
+        SingleFlight {
+            calls: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+// Removes the leader's entry if its future is dropped before finishing, so
+// the waiters wake up and retry instead of hanging.
+struct Leader<'a, K: Eq + Hash, T> {
+    flight: &'a SingleFlight<K, T>,
+    key: K,
+    finished: bool,
+}
+
+impl<K: Eq + Hash, T> Leader<'_, K, T> {
+    fn finish(mut self) -> Vec<Waiter<T>> {
+        self.finished = true;
+        let mut calls = self.flight.calls.lock().unwrap();
+        calls.remove(&self.key).unwrap_or_default()
+    }
+}
+
+impl<K: Eq + Hash, T> Drop for Leader<'_, K, T> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.flight.calls.lock().unwrap().remove(&self.key);
+        }
+    }
+}
+
+impl<K: Eq + Hash + Clone, T: Clone> SingleFlight<K, T> {
+    pub(crate) async fn run<F, Fut>(&self, key: K, call: F) -> Result<T, TransportError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, TransportError>>,
+    {
+        loop {
+            let receiver = {
+                let mut calls = self.calls.lock().unwrap();
+                match calls.get_mut(&key) {
+                    Some(waiters) => {
+                        let (sender, receiver) = oneshot::channel();
+                        waiters.push(sender);
+                        receiver
+                    }
+                    None => {
+                        calls.insert(key.clone(), Vec::new());
+                        break;
+                    }
+                }
+            };
+            match receiver.await {
+                Ok(result) => return result,
+                // The leading caller was dropped; one of the waiters takes over.
+                Err(oneshot::Canceled) => continue,
+            }
+        }
+
+        let leader = Leader {
+            flight: self,
+            key,
+            finished: false,
+        };
+        let result = call().await;
+        for waiter in leader.finish() {
+            let _ = waiter.send(result.clone());
+        }
+        result
+    }
+}
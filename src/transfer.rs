@@ -0,0 +1,242 @@
+use serde::{Deserialize, Serialize};
+
+use crate::oklink::{
+    BatchInternalTransaction, BatchInternalTransactionList, BatchNormalTransaction,
+    BatchNormalTransactionList, BatchTokenTransactionList, InternalTransaction,
+    InternalTransactionList, NormalTransaction, NormalTransactionList, TokenTransaction,
+    TokenTransactionList,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferKind {
+    // A transaction moving the chain's native asset.
+    Native,
+    // A value transfer made by a contract call inside a transaction.
+    Internal,
+    Token,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferStatus {
+    Success,
+    Failed,
+    Pending,
+    // The endpoint doesn't report a status.
+    Unknown,
+}
+
+impl TransferStatus {
+    fn from_state(state: &str) -> Self {
+        match state {
+            "success" => TransferStatus::Success,
+            "fail" | "failed" => TransferStatus::Failed,
+            "pending" => TransferStatus::Pending,
+            _ => TransferStatus::Unknown,
+        }
+    }
+}
+
+// One movement of value, whichever list endpoint it came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transfer {
+    pub kind: TransferKind,
+    pub from: String,
+    pub to: String,
+    // Symbol of the native asset or token.
+    pub asset: String,
+    // Empty for native and internal transfers.
+    pub token_contract_address: String,
+    pub token_id: String,
+    // As returned by the API.
+    pub amount: String,
+    pub tx_hash: String,
+    // Log index for token transfers, trace index for internal ones. The
+    // list endpoints don't return it, so it is only set by callers that
+    // know it.
+    pub index: Option<u32>,
+    // `None` when the endpoint left the field empty or malformed.
+    pub height: Option<u64>,
+    // Unix time in milliseconds; `None` like `height`.
+    pub time: Option<u64>,
+    pub status: TransferStatus,
+}
+
+impl Transfer {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        kind: TransferKind,
+        from: &str,
+        to: &str,
+        asset: &str,
+        amount: &str,
+        tx_hash: &str,
+        height: &str,
+        time: &str,
+        status: TransferStatus,
+    ) -> Self {
+        Transfer {
+            kind,
+            from: from.to_string(),
+            to: to.to_string(),
+            asset: asset.to_string(),
+            token_contract_address: String::new(),
+            token_id: String::new(),
+            amount: amount.to_string(),
+            tx_hash: tx_hash.to_string(),
+            index: None,
+            height: height.parse().ok(),
+            time: time.parse().ok(),
+            status,
+        }
+    }
+
+    // Whether `address` sent or received this transfer.
+    pub fn involves(&self, address: &str) -> bool {
+        self.from.eq_ignore_ascii_case(address) || self.to.eq_ignore_ascii_case(address)
+    }
+}
+
+impl From<&NormalTransaction> for Transfer {
+    fn from(tx: &NormalTransaction) -> Self {
+        Transfer::new(
+            TransferKind::Native,
+            &tx.from,
+            &tx.to,
+            &tx.symbol,
+            &tx.amount,
+            &tx.tx_id,
+            &tx.height,
+            &tx.transaction_time,
+            TransferStatus::from_state(&tx.state),
+        )
+    }
+}
+
+impl From<&BatchNormalTransaction> for Transfer {
+    fn from(tx: &BatchNormalTransaction) -> Self {
+        Transfer::new(
+            TransferKind::Native,
+            &tx.from,
+            &tx.to,
+            &tx.symbol,
+            &tx.amount,
+            &tx.tx_id,
+            &tx.height,
+            &tx.transaction_time,
+            TransferStatus::from_state(&tx.state),
+        )
+    }
+}
+
+impl From<&InternalTransaction> for Transfer {
+    fn from(tx: &InternalTransaction) -> Self {
+        Transfer::new(
+            TransferKind::Internal,
+            &tx.from,
+            &tx.to,
+            &tx.symbol,
+            &tx.amount,
+            &tx.tx_id,
+            &tx.height,
+            &tx.transaction_time,
+            TransferStatus::from_state(&tx.state),
+        )
+    }
+}
+
+// The batch endpoint returns neither a symbol nor a state.
+impl From<&BatchInternalTransaction> for Transfer {
+    fn from(tx: &BatchInternalTransaction) -> Self {
+        Transfer::new(
+            TransferKind::Internal,
+            &tx.from,
+            &tx.to,
+            "",
+            &tx.amount,
+            &tx.tx_id,
+            &tx.height,
+            &tx.transaction_time,
+            TransferStatus::Unknown,
+        )
+    }
+}
+
+// Token transfers are emitted by successful transactions only.
+impl From<&TokenTransaction> for Transfer {
+    fn from(tx: &TokenTransaction) -> Self {
+        Transfer {
+            token_contract_address: tx.token_contract_address.clone(),
+            token_id: tx.token_id.clone(),
+            ..Transfer::new(
+                TransferKind::Token,
+                &tx.from,
+                &tx.to,
+                &tx.symbol,
+                &tx.amount,
+                &tx.tx_id,
+                &tx.height,
+                &tx.transaction_time,
+                TransferStatus::Success,
+            )
+        }
+    }
+}
+
+impl NormalTransactionList {
+    pub fn transfers(&self) -> Vec<Transfer> {
+        self.data
+            .transaction_list
+            .iter()
+            .map(Transfer::from)
+            .collect()
+    }
+}
+
+impl InternalTransactionList {
+    pub fn transfers(&self) -> Vec<Transfer> {
+        self.data
+            .transaction_list
+            .iter()
+            .map(Transfer::from)
+            .collect()
+    }
+}
+
+impl TokenTransactionList {
+    pub fn transfers(&self) -> Vec<Transfer> {
+        self.data
+            .transaction_lists
+            .iter()
+            .map(Transfer::from)
+            .collect()
+    }
+}
+
+impl BatchNormalTransactionList {
+    pub fn transfers(&self) -> Vec<Transfer> {
+        self.data
+            .iter()
+            .flat_map(|data| data.transaction_list.iter().map(Transfer::from))
+            .collect()
+    }
+}
+
+impl BatchInternalTransactionList {
+    pub fn transfers(&self) -> Vec<Transfer> {
+        self.data
+            .iter()
+            .flat_map(|data| data.transaction_list.iter().map(Transfer::from))
+            .collect()
+    }
+}
+
+impl BatchTokenTransactionList {
+    pub fn transfers(&self) -> Vec<Transfer> {
+        self.data
+            .iter()
+            .flat_map(|data| data.transaction_list.iter().map(Transfer::from))
+            .collect()
+    }
+}
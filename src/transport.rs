@@ -0,0 +1,160 @@
+#[cfg(not(target_arch = "wasm32"))]
+use futures::future::BoxFuture;
+#[cfg(target_arch = "wasm32")]
+use futures::future::LocalBoxFuture;
+use reqwest::header::HeaderValue;
+use reqwest::Client;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::secret::ApiKey;
+
+// Cheap to clone, so a request shared by several identical calls can hand
+// each caller the same error.
+#[derive(Debug, Clone)]
+pub enum TransportError {
+    Http(Arc<reqwest::Error>),
+    // Failures reported by custom transports.
+    Other(Arc<dyn std::error::Error + Send + Sync>),
+    Json(Arc<serde_json::Error>),
+    // Every API key is disabled or at its rate limit.
+    RateLimited { retry_after: Duration },
+    // Rejected before sending, e.g. too many addresses for a batch endpoint.
+    InvalidArgument(String),
+    // The API answered with a non-zero `code`, e.g. a quota error.
+    Api { code: String, msg: String },
+}
+
+impl TransportError {
+    pub fn other(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        TransportError::Other(Arc::from(error.into()))
+    }
+}
+
+impl From<reqwest::Error> for TransportError {
+    fn from(e: reqwest::Error) -> Self {
+        TransportError::Http(Arc::new(e))
+    }
+}
+
+impl From<serde_json::Error> for TransportError {
+    fn from(e: serde_json::Error) -> Self {
+        TransportError::Json(Arc::new(e))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpMethod {
+    Get,
+    Post,
+}
+
+// The header OKLink reads the API key from.
+pub const API_KEY_HEADER: &str = "Ok-Access-Key";
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    pub method: HttpMethod,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    // Sent in the `API_KEY_HEADER` header. The key stays an `ApiKey` until
+    // the transport builds the outgoing request, so middleware and copies of
+    // the request never hold it as a plain string. Transports add the header
+    // themselves.
+    pub api_key: Option<ApiKey>,
+    pub query: Vec<(String, String)>,
+    pub body: Option<Vec<u8>>,
+}
+
+// Headers that carry credentials and are redacted when a request is printed.
+const SECRET_HEADERS: &[&str] = &["Ok-Access-Key", "Authorization"];
+
+impl fmt::Debug for HttpRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let headers: Vec<(&str, &str)> = self
+            .headers
+            .iter()
+            .map(|(name, value)| {
+                let secret = SECRET_HEADERS
+                    .iter()
+                    .any(|secret| secret.eq_ignore_ascii_case(name));
+                (name.as_str(), if secret { "***" } else { value.as_str() })
+            })
+            .collect();
+        f.debug_struct("HttpRequest")
+            .field("method", &self.method)
+            .field("url", &self.url)
+            .field("headers", &headers)
+            .field("api_key", &self.api_key)
+            .field("query", &self.query)
+            .field("body", &self.body)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+// reqwest's wasm futures are not `Send`.
+#[cfg(not(target_arch = "wasm32"))]
+pub type TransportFuture<'a> = BoxFuture<'a, Result<HttpResponse, TransportError>>;
+#[cfg(target_arch = "wasm32")]
+pub type TransportFuture<'a> = LocalBoxFuture<'a, Result<HttpResponse, TransportError>>;
+
+// Everything the client sends goes through a transport, so the HTTP stack and
+// async runtime can be swapped out, or replaced by a test double.
+pub trait HttpTransport: Send + Sync {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_>;
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: Client,
+}
+
+impl ReqwestTransport {
+    pub fn new() -> Self {
+        ReqwestTransport::default()
+    }
+
+    pub fn from_client(client: Client) -> Self {
+        ReqwestTransport { client }
+    }
+}
+
+impl HttpTransport for ReqwestTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let mut builder = match request.method {
+                HttpMethod::Get => self.client.get(&request.url),
+                HttpMethod::Post => self.client.post(&request.url),
+            };
+            for (name, value) in &request.headers {
+                builder = builder.header(name, value);
+            }
+            if let Some(api_key) = &request.api_key {
+                let mut value =
+                    HeaderValue::from_str(api_key.expose()).map_err(TransportError::other)?;
+                // Keeps the key out of HTTP/2 header compression tables and
+                // out of reqwest's debug output.
+                value.set_sensitive(true);
+                builder = builder.header(API_KEY_HEADER, value);
+            }
+            if !request.query.is_empty() {
+                builder = builder.query(&request.query);
+            }
+            if let Some(body) = request.body {
+                builder = builder.body(body);
+            }
+            let response = builder.send().await?;
+            Ok(HttpResponse {
+                status: response.status().as_u16(),
+                body: response.bytes().await?.to_vec(),
+            })
+        })
+    }
+}
@@ -1,6 +1,6 @@
 use serde::Serialize;
 
-#[derive(Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub enum ProtocolType {
     #[serde(rename = "token_20")]
     Token20,
@@ -8,4 +8,14 @@ pub enum ProtocolType {
     Token721,
     #[serde(rename = "token_1155")]
     Token1155,
-}
\ No newline at end of file
+}
+
+impl ProtocolType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProtocolType::Token20 => "token_20",
+            ProtocolType::Token721 => "token_721",
+            ProtocolType::Token1155 => "token_1155",
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+
+use crate::clock::unix_time_ms;
+use crate::oklink::{Oklink, TokenBalance, TokenTransaction};
+use crate::transport::TransportError;
+
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+// The multi-token price endpoint accepts up to 20 addresses.
+const PRICE_BATCH: usize = 20;
+
+// Native KAIA has no contract address; it is priced through wrapped KAIA.
+const WRAPPED_NATIVE_TOKEN: &str = "0x19aac5f612f524b754ca7e7c41cbfa2e981a4432";
+
+#[derive(Debug, Clone)]
+pub struct ValuedTransfer {
+    pub transfer: TokenTransaction,
+    pub price_usd: Option<f64>,
+    pub value_usd: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ValuedBalance {
+    pub balance: TokenBalance,
+    pub price_usd: Option<f64>,
+    pub value_usd: Option<f64>,
+}
+
+pub(crate) fn price_token(token_contract_address: &str) -> String {
+    if token_contract_address.is_empty() {
+        WRAPPED_NATIVE_TOKEN.to_string()
+    } else {
+        token_contract_address.to_lowercase()
+    }
+}
+
+fn value(amount: &str, price_usd: Option<f64>) -> Option<f64> {
+    price_usd
+        .zip(amount.parse::<f64>().ok())
+        .map(|(price, amount)| price * amount)
+}
+
+impl Oklink {
+    // Current USD prices keyed by `price_token`, so native KAIA is found
+    // under wrapped KAIA. Tokens without a price are absent.
+    pub(crate) async fn usd_prices<'a>(
+        &self,
+        token_contract_addresses: impl IntoIterator<Item = &'a str>,
+    ) -> Result<HashMap<String, f64>, TransportError> {
+        let mut tokens: Vec<String> = token_contract_addresses
+            .into_iter()
+            .map(price_token)
+            .collect();
+        tokens.sort_unstable();
+        tokens.dedup();
+
+        let mut prices: HashMap<String, f64> = HashMap::new();
+        for chunk in tokens.chunks(PRICE_BATCH) {
+            let chunk: Vec<&str> = chunk.iter().map(String::as_str).collect();
+            for price in self.token_price(&chunk).await?.data {
+                if let Ok(last_price) = price.last_price.parse::<f64>() {
+                    prices.insert(price.token_contract_address.to_lowercase(), last_price);
+                }
+            }
+        }
+        Ok(prices)
+    }
+
+    // Daily close price in USD for the UTC day containing `timestamp_ms`.
+    // Both hits and misses are cached per token per day, except for the
+    // current day, whose price is still moving.
+    pub async fn price_at(
+        &self,
+        token_contract_address: &str,
+        timestamp_ms: u64,
+    ) -> Result<Option<f64>, TransportError> {
+        let token = price_token(token_contract_address);
+        let day = timestamp_ms / DAY_MS;
+        if let Some(price) = self.daily_prices.lock().unwrap().get(&(token.clone(), day)) {
+            return Ok(*price);
+        }
+        // `after` returns records older than the timestamp, newest first, so
+        // the first one is the day's close unless the day has no record.
+        let after = ((day + 1) * DAY_MS).to_string();
+        let history = self
+            .token_price_history(&token, Some("1d"), Some(&after), None, Some("1"))
+            .await?;
+        // Error bodies also parse, with no data; they must not be cached as
+        // "no price".
+        if history.code != "0" {
+            return Err(TransportError::Api {
+                code: history.code,
+                msg: history.msg,
+            });
+        }
+        let price = history
+            .data
+            .first()
+            .filter(|point| {
+                point
+                    .time
+                    .parse::<u64>()
+                    .is_ok_and(|time| time / DAY_MS == day)
+            })
+            .and_then(|point| point.price.parse::<f64>().ok());
+        if day < unix_time_ms() / DAY_MS {
+            self.daily_prices
+                .lock()
+                .unwrap()
+                .insert((token, day), price);
+        }
+        Ok(price)
+    }
+
+    pub async fn value_transfers(
+        &self,
+        transfers: Vec<TokenTransaction>,
+    ) -> Result<Vec<ValuedTransfer>, TransportError> {
+        let mut valued = Vec::with_capacity(transfers.len());
+        for transfer in transfers {
+            let price_usd = match transfer.transaction_time.parse::<u64>() {
+                Ok(time) => {
+                    self.price_at(&transfer.token_contract_address, time)
+                        .await?
+                }
+                Err(_) => None,
+            };
+            valued.push(ValuedTransfer {
+                value_usd: value(&transfer.amount, price_usd),
+                price_usd,
+                transfer,
+            });
+        }
+        Ok(valued)
+    }
+
+    pub async fn value_balances(
+        &self,
+        balances: Vec<TokenBalance>,
+    ) -> Result<Vec<ValuedBalance>, TransportError> {
+        let prices = self
+            .usd_prices(balances.iter().map(|b| b.token_contract_address.as_str()))
+            .await?;
+
+        Ok(balances
+            .into_iter()
+            .map(|balance| {
+                let price_usd = prices
+                    .get(&price_token(&balance.token_contract_address))
+                    .copied();
+                ValuedBalance {
+                    value_usd: value(&balance.holding_amount, price_usd),
+                    price_usd,
+                    balance,
+                }
+            })
+            .collect())
+    }
+
+    pub async fn valued_token_transaction_list(
+        &self,
+        address: &str,
+        protocol_type: &str,
+        token_contract_address: Option<&str>,
+        page: Option<&str>,
+        limit: Option<&str>,
+    ) -> Result<Vec<ValuedTransfer>, TransportError> {
+        let list = self
+            .address_token_transaction_list(
+                address,
+                protocol_type,
+                token_contract_address,
+                page,
+                limit,
+            )
+            .await?;
+        self.value_transfers(list.data.transaction_lists).await
+    }
+}
@@ -0,0 +1,488 @@
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::oklink::{Oklink, TokenTransaction, TokenTransfer};
+use crate::transport::TransportError;
+
+const SIGNATURE_HEADER: &str = "X-Oklink-Signature";
+const IDEMPOTENCY_HEADER: &str = "Idempotency-Key";
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+// How many delivered events are remembered for deduplication.
+const DELIVERED_CAPACITY: usize = 10_000;
+
+#[derive(Debug)]
+pub enum WebhookError {
+    Http(TransportError),
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+
+impl From<TransportError> for WebhookError {
+    fn from(e: TransportError) -> Self {
+        WebhookError::Http(e)
+    }
+}
+
+impl From<std::io::Error> for WebhookError {
+    fn from(e: std::io::Error) -> Self {
+        WebhookError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for WebhookError {
+    fn from(e: serde_json::Error) -> Self {
+        WebhookError::Json(e)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum WebhookEventKind {
+    #[serde(rename = "address_token_transfer")]
+    AddressTokenTransfer,
+    #[serde(rename = "token_transfer")]
+    TokenTransfer,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookEvent {
+    pub idempotency_key: String,
+    pub kind: WebhookEventKind,
+    pub watched: String,
+    pub tx_id: String,
+    pub height: String,
+    pub transaction_time: String,
+    pub from: String,
+    pub to: String,
+    pub token_contract_address: String,
+    pub token_id: String,
+    pub amount: String,
+    pub symbol: String,
+    // Transfer lists don't carry a log index, so identical transfers within
+    // one transaction are told apart by their position among each other.
+    #[serde(default)]
+    pub occurrence: u32,
+}
+
+impl WebhookEvent {
+    pub fn from_token_transaction(address: &str, tx: &TokenTransaction) -> Self {
+        let kind = WebhookEventKind::AddressTokenTransfer;
+        WebhookEvent {
+            idempotency_key: idempotency_key(
+                &kind,
+                address,
+                &tx.tx_id,
+                &tx.from,
+                &tx.to,
+                &tx.token_id,
+                &tx.amount,
+                0,
+            ),
+            kind,
+            watched: address.to_string(),
+            tx_id: tx.tx_id.clone(),
+            height: tx.height.clone(),
+            transaction_time: tx.transaction_time.clone(),
+            from: tx.from.clone(),
+            to: tx.to.clone(),
+            token_contract_address: tx.token_contract_address.clone(),
+            token_id: tx.token_id.clone(),
+            amount: tx.amount.clone(),
+            symbol: tx.symbol.clone(),
+            occurrence: 0,
+        }
+    }
+
+    pub fn from_token_transfer(token_contract_address: &str, tx: &TokenTransfer) -> Self {
+        let kind = WebhookEventKind::TokenTransfer;
+        WebhookEvent {
+            idempotency_key: idempotency_key(
+                &kind,
+                token_contract_address,
+                &tx.txid,
+                &tx.from,
+                &tx.to,
+                &tx.token_id,
+                &tx.amount,
+                0,
+            ),
+            kind,
+            watched: token_contract_address.to_string(),
+            tx_id: tx.txid.clone(),
+            height: tx.height.clone(),
+            transaction_time: tx.transaction_time.clone(),
+            from: tx.from.clone(),
+            to: tx.to.clone(),
+            token_contract_address: tx.token_contract_address.clone(),
+            token_id: tx.token_id.clone(),
+            amount: tx.amount.clone(),
+            symbol: tx.transaction_symbol.clone(),
+            occurrence: 0,
+        }
+    }
+
+    fn with_occurrence(mut self, occurrence: u32) -> Self {
+        self.occurrence = occurrence;
+        self.idempotency_key = idempotency_key(
+            &self.kind,
+            &self.watched,
+            &self.tx_id,
+            &self.from,
+            &self.to,
+            &self.token_id,
+            &self.amount,
+            occurrence,
+        );
+        self
+    }
+}
+
+// Numbers repeated transfers in one page, e.g. two identical payouts in a
+// batch transaction, so each gets its own idempotency key. The first one
+// keeps the plain key.
+pub fn number_occurrences(events: Vec<WebhookEvent>) -> Vec<WebhookEvent> {
+    let mut seen: HashMap<String, u32> = HashMap::new();
+    events
+        .into_iter()
+        .map(|event| {
+            let count = seen.entry(event.idempotency_key.clone()).or_default();
+            let occurrence = *count;
+            *count += 1;
+            if occurrence == 0 {
+                event
+            } else {
+                event.with_occurrence(occurrence)
+            }
+        })
+        .collect()
+}
+
+// The key only depends on the transfer itself, so re-polling the same page
+// yields the same key and receivers can safely drop duplicates.
+#[allow(clippy::too_many_arguments)]
+fn idempotency_key(
+    kind: &WebhookEventKind,
+    watched: &str,
+    tx_id: &str,
+    from: &str,
+    to: &str,
+    token_id: &str,
+    amount: &str,
+    occurrence: u32,
+) -> String {
+    let mut hasher = Sha256::new();
+    for part in [
+        serde_json::to_string(kind).unwrap().as_str(),
+        watched,
+        tx_id,
+        from,
+        to,
+        token_id,
+        amount,
+    ] {
+        hasher.update(part.as_bytes());
+        hasher.update([0u8]);
+    }
+    if occurrence > 0 {
+        hasher.update(occurrence.to_be_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[derive(Debug, Clone)]
+pub struct WebhookEndpoint {
+    pub url: String,
+    pub secret: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub url: String,
+    pub attempts: u32,
+    pub error: String,
+    pub event: WebhookEvent,
+}
+
+pub struct WebhookDispatcher {
+    client: Client,
+    endpoints: Vec<WebhookEndpoint>,
+    dead_letter_dir: PathBuf,
+    max_attempts: u32,
+    initial_backoff: Duration,
+    delivered: HashSet<String>,
+    delivered_order: VecDeque<String>,
+    delivered_capacity: usize,
+    delivered_log: Option<PathBuf>,
+    // Lines in the delivered log, including ones already forgotten.
+    delivered_log_lines: usize,
+}
+
+impl WebhookDispatcher {
+    pub fn new(endpoints: Vec<WebhookEndpoint>, dead_letter_dir: PathBuf) -> Self {
+        WebhookDispatcher {
+            client: client(DEFAULT_TIMEOUT),
+            endpoints,
+            dead_letter_dir,
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(500),
+            delivered: HashSet::new(),
+            delivered_order: VecDeque::new(),
+            delivered_capacity: DELIVERED_CAPACITY,
+            delivered_log: None,
+            delivered_log_lines: 0,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client = client(timeout);
+        self
+    }
+
+    // Once more events than this have been delivered, the oldest are
+    // forgotten and are sent again if they reappear in a poll. Set it before
+    // `with_delivered_log`, which loads at most this many keys.
+    pub fn with_delivered_capacity(mut self, capacity: usize) -> Self {
+        self.delivered_capacity = capacity.max(1);
+        self
+    }
+
+    // Without a log, delivered events are only remembered in memory and are
+    // sent again after a restart. With one, the key of every delivered event
+    // is appended to `path`, and keys already in it are loaded here. The log
+    // is rewritten with only the remembered keys once it grows past twice the
+    // delivered capacity.
+    pub fn with_delivered_log(mut self, path: PathBuf) -> Result<Self, WebhookError> {
+        match fs::read_to_string(&path) {
+            Ok(log) => {
+                for key in log.lines() {
+                    self.remember(key.to_string());
+                    self.delivered_log_lines += 1;
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        self.delivered_log = Some(path);
+        Ok(self)
+    }
+
+    pub fn with_retry(mut self, max_attempts: u32, initial_backoff: Duration) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    pub async fn poll_address(
+        &mut self,
+        oklink: &Oklink,
+        address: &str,
+        protocol_type: &str,
+        token_contract_address: Option<&str>,
+    ) -> Result<Vec<WebhookEvent>, WebhookError> {
+        let list = oklink
+            .address_token_transaction_list(
+                address,
+                protocol_type,
+                token_contract_address,
+                None,
+                None,
+            )
+            .await?;
+        let events = list
+            .data
+            .transaction_lists
+            .iter()
+            .map(|tx| WebhookEvent::from_token_transaction(address, tx))
+            .collect();
+        let events = number_occurrences(events);
+        self.dispatch_all(events).await
+    }
+
+    pub async fn poll_token(
+        &mut self,
+        oklink: &Oklink,
+        token_contract_address: &str,
+        min_amount: Option<&str>,
+    ) -> Result<Vec<WebhookEvent>, WebhookError> {
+        let list = oklink
+            .token_transfer_details(token_contract_address, None, min_amount, None, None)
+            .await?;
+        let events = list
+            .data
+            .transaction_list
+            .iter()
+            .map(|tx| WebhookEvent::from_token_transfer(token_contract_address, tx))
+            .collect();
+        let events = number_occurrences(events);
+        self.dispatch_all(events).await
+    }
+
+    // Dispatches every event that has not been delivered before and returns
+    // the new ones.
+    pub async fn dispatch_all(
+        &mut self,
+        events: Vec<WebhookEvent>,
+    ) -> Result<Vec<WebhookEvent>, WebhookError> {
+        let mut dispatched = Vec::new();
+        for event in events {
+            if self.delivered.contains(&event.idempotency_key) {
+                continue;
+            }
+            self.dispatch(&event).await?;
+            dispatched.push(event);
+        }
+        Ok(dispatched)
+    }
+
+    // Delivers an event to every endpoint, writing a dead letter for each
+    // endpoint that still fails after the last retry.
+    pub async fn dispatch(&mut self, event: &WebhookEvent) -> Result<(), WebhookError> {
+        let body = serde_json::to_vec(event)?;
+        for endpoint in &self.endpoints {
+            if let Err(error) = self.deliver(endpoint, &event.idempotency_key, &body).await {
+                let letter = DeadLetter {
+                    url: endpoint.url.clone(),
+                    attempts: self.max_attempts,
+                    error,
+                    event: event.clone(),
+                };
+                self.write_dead_letter(&letter)?;
+            }
+        }
+        self.remember(event.idempotency_key.clone());
+        if let Some(path) = &self.delivered_log {
+            if self.delivered_log_lines >= self.delivered_capacity * 2 {
+                let keys: String = self
+                    .delivered_order
+                    .iter()
+                    .map(|key| format!("{}\n", key))
+                    .collect();
+                fs::write(path, keys)?;
+                self.delivered_log_lines = self.delivered_order.len();
+            } else {
+                let mut log = OpenOptions::new().create(true).append(true).open(path)?;
+                writeln!(log, "{}", event.idempotency_key)?;
+                self.delivered_log_lines += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn remember(&mut self, key: String) {
+        if self.delivered.insert(key.clone()) {
+            self.delivered_order.push_back(key);
+        }
+        while self.delivered_order.len() > self.delivered_capacity {
+            if let Some(oldest) = self.delivered_order.pop_front() {
+                self.delivered.remove(&oldest);
+            }
+        }
+    }
+
+    async fn deliver(
+        &self,
+        endpoint: &WebhookEndpoint,
+        idempotency_key: &str,
+        body: &[u8],
+    ) -> Result<(), String> {
+        let signature = sign(&endpoint.secret, body);
+        let mut backoff = self.initial_backoff;
+        let mut last_error = String::new();
+        for attempt in 1..=self.max_attempts {
+            let response = self
+                .client
+                .post(&endpoint.url)
+                .header("Content-Type", "application/json")
+                .header(SIGNATURE_HEADER, &signature)
+                .header(IDEMPOTENCY_HEADER, idempotency_key)
+                .body(body.to_vec())
+                .send()
+                .await;
+            match response {
+                Ok(response) if response.status().is_success() => return Ok(()),
+                Ok(response) => last_error = format!("unexpected status {}", response.status()),
+                Err(e) => last_error = e.to_string(),
+            }
+            if attempt < self.max_attempts {
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+        }
+        Err(last_error)
+    }
+
+    fn write_dead_letter(&self, letter: &DeadLetter) -> Result<(), WebhookError> {
+        fs::create_dir_all(&self.dead_letter_dir)?;
+        let endpoint_key = hex::encode(Sha256::digest(letter.url.as_bytes()));
+        let path = self.dead_letter_dir.join(format!(
+            "{}-{}.json",
+            letter.event.idempotency_key,
+            &endpoint_key[..8]
+        ));
+        fs::write(path, serde_json::to_vec_pretty(letter)?)?;
+        Ok(())
+    }
+
+    pub fn dead_letters(&self) -> Result<Vec<DeadLetter>, WebhookError> {
+        let mut letters = Vec::new();
+        if !self.dead_letter_dir.exists() {
+            return Ok(letters);
+        }
+        for entry in fs::read_dir(&self.dead_letter_dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                letters.push(serde_json::from_slice(&fs::read(path)?)?);
+            }
+        }
+        Ok(letters)
+    }
+
+    // Retries every dead letter once more, removing the ones that now succeed.
+    pub async fn redeliver_dead_letters(&self) -> Result<usize, WebhookError> {
+        let mut redelivered = 0;
+        if !self.dead_letter_dir.exists() {
+            return Ok(redelivered);
+        }
+        for entry in fs::read_dir(&self.dead_letter_dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let letter: DeadLetter = serde_json::from_slice(&fs::read(&path)?)?;
+            let endpoint = match self.endpoints.iter().find(|e| e.url == letter.url) {
+                Some(endpoint) => endpoint,
+                None => continue,
+            };
+            let body = serde_json::to_vec(&letter.event)?;
+            if self
+                .deliver(endpoint, &letter.event.idempotency_key, &body)
+                .await
+                .is_ok()
+            {
+                fs::remove_file(&path)?;
+                redelivered += 1;
+            }
+        }
+        Ok(redelivered)
+    }
+}
+
+fn client(timeout: Duration) -> Client {
+    Client::builder()
+        .timeout(timeout)
+        .build()
+        .expect("reqwest client with timeout")
+}
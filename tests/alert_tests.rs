@@ -0,0 +1,130 @@
+use serde_json::{json, Value};
+
+use oklink::alert::{AlertError, AlertMonitor, AlertRule, Threshold};
+use oklink::transport::{HttpRequest, HttpResponse, HttpTransport, TransportFuture};
+use oklink::Oklink;
+
+fn transfer(tx_id: &str, from: &str, amount: &str) -> Value {
+    json!({
+        "txid": tx_id,
+        "block_hash": "0xBlock",
+        "height": "100",
+        "transaction_time": "1700000000000",
+        "from": from,
+        "to": "0xBob",
+        "is_from_contract": false,
+        "is_to_contract": false,
+        "amount": amount,
+        "transaction_symbol": "USDT",
+        "method_id": "",
+        "token_contract_address": "0xUsdt",
+        "protocol_type": "token_20",
+        "state": "success",
+        "token_id": ""
+    })
+}
+
+// Serves two USDT transfers, a label for 0xExchange and, if set, a price.
+struct AlertTransport {
+    price: Option<&'static str>,
+}
+
+impl HttpTransport for AlertTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        let data = if request.url.ends_with("token/transaction-list") {
+            json!({
+                "page": "1",
+                "limit": "20",
+                "total_page": "1",
+                "chain_full_name": "Kaia",
+                "chain_short_name": "KAIA",
+                "transaction_list": [
+                    transfer("0xA", "0xExchange", "100"),
+                    transfer("0xB", "0xAlice", "10")
+                ]
+            })
+        } else if request.url.ends_with("price-multi") {
+            let prices: Vec<Value> = self
+                .price
+                .iter()
+                .map(|price| {
+                    json!({
+                        "chain_id": "8217",
+                        "token_contract_address": "0xusdt",
+                        "last_price": price,
+                        "price_change_24h": "0"
+                    })
+                })
+                .collect();
+            json!(prices)
+        } else {
+            json!([{"label": "Binance: Hot Wallet", "address": "0xexchange"}])
+        };
+        let body = json!({"code": "0", "msg": "", "data": data});
+        Box::pin(async move {
+            Ok(HttpResponse {
+                status: 200,
+                body: body.to_string().into_bytes(),
+            })
+        })
+    }
+}
+
+fn rule(min_amount: Threshold, labels: &[&str]) -> AlertRule {
+    AlertRule {
+        name: "usdt".to_string(),
+        token_contract_address: Some("0xUsdt".to_string()),
+        min_amount,
+        labels: labels.iter().map(|label| label.to_string()).collect(),
+    }
+}
+
+#[tokio::test]
+async fn test_alert_rule_matches_labels_and_dedups() {
+    let oklink = Oklink::with_transport("key", AlertTransport { price: None });
+    let mut monitor = AlertMonitor::new(vec![rule(Threshold::Units(5.0), &["binance"])]);
+
+    let events = monitor.poll(&oklink).await.unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].tx_id, "0xA");
+    assert_eq!(events[0].from_label.as_deref(), Some("Binance: Hot Wallet"));
+
+    assert!(monitor.poll(&oklink).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_alert_seen_set_is_bounded() {
+    let oklink = Oklink::with_transport("key", AlertTransport { price: None });
+    let mut monitor =
+        AlertMonitor::new(vec![rule(Threshold::Units(5.0), &[])]).with_seen_capacity(1);
+
+    assert_eq!(monitor.poll(&oklink).await.unwrap().len(), 2);
+    let events = monitor.poll(&oklink).await.unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].tx_id, "0xA");
+}
+
+#[tokio::test]
+async fn test_alert_usd_threshold_refreshes_price() {
+    let oklink = Oklink::with_transport("key", AlertTransport { price: Some("2") });
+    let mut monitor = AlertMonitor::new(vec![rule(Threshold::Usd(150.0), &[])]);
+
+    let events = monitor.poll(&oklink).await.unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].tx_id, "0xA");
+    assert_eq!(events[0].amount_usd, Some(200.0));
+}
+
+#[tokio::test]
+async fn test_alert_usd_threshold_without_price_is_an_error() {
+    let oklink = Oklink::with_transport("key", AlertTransport { price: None });
+    let mut monitor = AlertMonitor::new(vec![rule(Threshold::Usd(150.0), &[])]);
+
+    match monitor.poll(&oklink).await {
+        Err(AlertError::MissingPrice(token)) => assert_eq!(token, "0xusdt"),
+        other => panic!("expected a missing price, got {:?}", other.map(|e| e.len())),
+    }
+
+    monitor.set_price("0xUsdt", 2.0);
+    assert_eq!(monitor.poll(&oklink).await.unwrap().len(), 1);
+}
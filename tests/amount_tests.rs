@@ -0,0 +1,98 @@
+mod common;
+
+use common::CannedTransport;
+use oklink::amount::{AmountError, TokenAmount};
+use oklink::Oklink;
+use serde_json::json;
+
+#[test]
+fn test_token_amount_converts_without_rounding() {
+    let amount = TokenAmount::from_units("1234.000000000000000001", 18)
+        .unwrap()
+        .with_symbol("KAIA");
+
+    assert_eq!(amount.raw(), 1_234_000_000_000_000_000_001);
+    assert_eq!(amount.to_units_string(), "1234.000000000000000001");
+    assert_eq!(amount.to_string(), "1234.000000000000000001 KAIA");
+
+    let raw = TokenAmount::from_raw_str("1500000", 6).unwrap();
+    assert_eq!(raw.to_units_string(), "1.5");
+    assert_eq!(TokenAmount::from_units("1.500", 6).unwrap(), raw);
+}
+
+#[test]
+fn test_token_amount_rejects_invalid_input() {
+    assert!(matches!(
+        TokenAmount::from_units("0.0000001", 6),
+        Err(AmountError::TooPrecise { .. })
+    ));
+    assert!(matches!(
+        TokenAmount::from_units("1e5", 6),
+        Err(AmountError::Invalid(_))
+    ));
+    assert!(matches!(
+        TokenAmount::from_units("-1", 6),
+        Err(AmountError::Invalid(_))
+    ));
+}
+
+#[test]
+fn test_token_amount_order_agrees_with_equality() {
+    let usdt = TokenAmount::from_units("1", 6).unwrap().with_symbol("USDT");
+    let usdc = TokenAmount::from_units("2", 6).unwrap().with_symbol("USDC");
+    let more_usdt = TokenAmount::from_units("2", 6).unwrap().with_symbol("USDT");
+
+    assert_eq!(usdt.partial_cmp(&usdc), None);
+    assert!(usdt < more_usdt);
+    assert!(usdt.checked_add(&usdc).is_none());
+    assert_eq!(usdt.checked_add(&usdt).unwrap(), more_usdt);
+}
+
+#[test]
+fn test_token_amount_reports_values_beyond_u128() {
+    // u128::MAX + 1
+    assert!(matches!(
+        TokenAmount::from_raw_str("340282366920938463463374607431768211456", 18),
+        Err(AmountError::Overflow(_))
+    ));
+    assert!(matches!(
+        TokenAmount::from_raw_str("12x", 18),
+        Err(AmountError::Invalid(_))
+    ));
+}
+
+fn token_info(token: &str, address: &str, precision: &str) -> serde_json::Value {
+    json!({
+        "token_full_name": token, "token": token, "precision": precision,
+        "token_contract_address": address, "protocol_type": "ERC20",
+        "address_count": "", "total_supply": "", "circulating_supply": "", "price": "",
+        "website": "", "total_market_cap": "", "issue_date": "",
+        "transaction_amount_24h": "", "tvl": "", "logo_url": ""
+    })
+}
+
+fn token_list_body(tokens: Vec<serde_json::Value>) -> String {
+    json!({
+        "code": "0", "msg": "",
+        "data": {"page": "1", "limit": "20", "total_page": "1", "token_list": tokens}
+    })
+    .to_string()
+}
+
+#[tokio::test]
+async fn test_token_meta_uses_the_requested_token() {
+    let body = token_list_body(vec![
+        token_info("WETH", "0xweth", "18"),
+        token_info("USDT", "0xusdt", "6"),
+    ]);
+    let oklink = Oklink::with_transport("key", CannedTransport::new(&body));
+
+    let meta = oklink.token_meta("0xUSDT").await.unwrap();
+    assert_eq!(meta.symbol, "USDT");
+    assert_eq!(meta.decimals, 6);
+
+    assert!(matches!(
+        oklink.token_meta("0xdai").await,
+        Err(AmountError::UnknownToken(address)) if address == "0xdai"
+    ));
+}
@@ -0,0 +1,122 @@
+use serde_json::{json, Value};
+
+use oklink::analytics::{compute_distribution, DistributionOptions};
+use oklink::transport::{HttpRequest, HttpResponse, HttpTransport, TransportFuture};
+use oklink::Oklink;
+
+#[test]
+fn test_holder_distribution_metrics() {
+    let holders = vec![
+        ("0xExchange".to_string(), 600.0),
+        ("0xWhale".to_string(), 300.0),
+        ("0xSmall".to_string(), 100.0),
+    ];
+    let labels = vec![("0xExchange".to_string(), "Binance: Hot Wallet".to_string())];
+    let options = DistributionOptions {
+        top_n: vec![1, 2],
+        bucket_bounds: vec![0.0, 500.0],
+        ..DistributionOptions::default()
+    };
+
+    let report = compute_distribution("0xToken", &holders, Some(1000.0), &labels, &options);
+
+    assert_eq!(report.holder_count, 3);
+    assert_eq!(report.nakamoto_coefficient, 1);
+    assert!((report.top_concentration[1].share - 0.9).abs() < 1e-9);
+    assert!((report.gini - 1.0 / 3.0).abs() < 1e-9);
+    assert_eq!(report.buckets[0].holders, 2);
+    assert_eq!(report.buckets[1].holders, 1);
+    assert!((report.exchange_share - 0.6).abs() < 1e-9);
+    assert!(report.to_json().unwrap().contains("\"nakamoto_coefficient\": 1"));
+}
+
+fn position(holder: &str, amount: &str, rank: &str) -> Value {
+    json!({
+        "holder_address": holder,
+        "amount": amount,
+        "value_usd": "0",
+        "position_change_24h": "0",
+        "rank": rank
+    })
+}
+
+// Two pages of holders; 0xGate is an exchange, 0xPool only contains "gate"
+// inside a word.
+struct HolderTransport;
+
+impl HttpTransport for HolderTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        let query = |key: &str| {
+            request
+                .query
+                .iter()
+                .find(|(k, _)| k == key)
+                .map_or(String::new(), |(_, value)| value.clone())
+        };
+        let endpoint = request.url.rsplit('/').next().unwrap_or_default();
+        let data = match endpoint {
+            "position-list" => {
+                let position_list = if query("page") == "1" {
+                    vec![position("0xGate", "500", "1"), position("0xPool", "300", "2")]
+                } else {
+                    vec![position("0xSmall", "200", "3")]
+                };
+                json!({
+                    "page": query("page"),
+                    "limit": "100",
+                    "total_page": "2",
+                    "circulating_supply": "1000",
+                    "position_list": position_list
+                })
+            }
+            "position-statistics" => json!({
+                "page": "1",
+                "limit": "20",
+                "total_page": "1",
+                "circulating_supply": "1000",
+                "position_list": [position(&query("holderAddress"), "300", "2")]
+            }),
+            _ => json!([
+                {"label": "Gate.io 3", "address": "0xgate"},
+                {"label": "Delegate Pool", "address": "0xpool"}
+            ]),
+        };
+        let body = json!({"code": "0", "msg": "", "data": data});
+        Box::pin(async move {
+            Ok(HttpResponse {
+                status: 200,
+                body: body.to_string().into_bytes(),
+            })
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_holder_distribution_pages_holders_and_matches_exchange_words() {
+    let oklink = Oklink::with_transport("key", HolderTransport);
+
+    let report = oklink
+        .holder_distribution("0xToken", &DistributionOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(report.holder_count, 3);
+    assert_eq!(report.circulating_supply, Some(1000.0));
+    assert_eq!(report.exchange_holders.len(), 1);
+    assert_eq!(report.exchange_holders[0].address, "0xGate");
+    assert!((report.exchange_share - 0.5).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_holder_position_uses_position_statistics() {
+    let oklink = Oklink::with_transport("key", HolderTransport);
+
+    let position = oklink
+        .holder_position("0xToken", "0xPool")
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(position.rank, "2");
+    assert_eq!(position.amount, "300");
+}
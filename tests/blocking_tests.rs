@@ -0,0 +1,69 @@
+#![cfg(feature = "blocking")]
+
+mod common;
+
+use common::CannedTransport;
+use mockito::mock;
+use oklink::blocking::Oklink;
+use oklink::feed::Activity;
+use oklink::kaia::AccountKey;
+use oklink::middleware::Metrics;
+
+#[test]
+fn test_blocking_account_key() {
+    let _m = mock("POST", "/rpc")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"jsonrpc": "2.0", "id": 1, "result": {"keyType": 1, "key": {}}}"#)
+        .create();
+
+    let oklink = Oklink::new("test_api_key".to_string());
+    let rpc_url = format!("{}/rpc", mockito::server_url());
+    let key = oklink.account_key("0xYourAddress", &rpc_url).unwrap();
+
+    assert!(matches!(key, Some(AccountKey::Legacy)));
+}
+
+#[test]
+fn test_blocking_get_request() {
+    let _m = mock("GET", "/api/v5/explorer/address/entity-labels")
+        .match_query(mockito::Matcher::UrlEncoded(
+            "address".to_string(),
+            "0xYourAddress".to_string(),
+        ))
+        .match_header("ok-access-key", "test_api_key")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"code": "0", "msg": "", "data": [{"label": "Binance: Hot Wallet", "address": "0xYourAddress"}]}"#,
+        )
+        .create();
+    let metrics = Metrics::new();
+
+    let oklink = Oklink::new("test_api_key".to_string())
+        .with_base_url(&mockito::server_url())
+        .with_middleware(metrics.clone());
+    let labels = oklink.address_entity_labels("0xYourAddress").unwrap();
+
+    assert_eq!(labels.data[0].label, "Binance: Hot Wallet");
+    assert_eq!(oklink.key_stats()[0].requests, 1);
+    assert_eq!(metrics.total().requests, 1);
+}
+
+#[test]
+fn test_blocking_activity_feed() {
+    let transport = CannedTransport::new(
+        r#"{"code": "0", "msg": "", "data": {"page": "1", "limit": "50", "total_page": "1", "transaction_list": [], "transaction_lists": []}}"#,
+    );
+    let requests = transport.requests.clone();
+    let oklink = Oklink::with_transport("test_api_key", transport);
+
+    let feed: Vec<Activity> = oklink
+        .activity_feed("0xYourAddress", None)
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert!(feed.is_empty());
+    // Normal, internal and the three token lists.
+    assert_eq!(requests.lock().unwrap().len(), 5);
+}
@@ -0,0 +1,87 @@
+// Transports shared by the integration tests. Each test binary uses only some
+// of them.
+#![allow(dead_code)]
+
+use std::sync::{Arc, Mutex};
+
+use oklink::transport::{HttpRequest, HttpResponse, HttpTransport, TransportFuture};
+
+pub const OK_BODY: &str = r#"{"code": "0", "msg": "", "data": []}"#;
+pub const AUTH_ERROR_BODY: &str =
+    r#"{"code": "50111", "msg": "Invalid OK-ACCESS-KEY", "data": []}"#;
+pub const RATE_LIMIT_ERROR_BODY: &str =
+    r#"{"code": "50011", "msg": "Too Many Requests", "data": []}"#;
+
+// Answers every request with the same body and records what was sent.
+// Requests with one of the `overrides` values in their query get the paired
+// body instead.
+pub struct CannedTransport {
+    body: String,
+    overrides: Vec<(String, String)>,
+    pub requests: Arc<Mutex<Vec<HttpRequest>>>,
+}
+
+impl CannedTransport {
+    pub fn new(body: &str) -> Self {
+        CannedTransport {
+            body: body.to_string(),
+            overrides: Vec::new(),
+            requests: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn ok() -> Self {
+        CannedTransport::new(OK_BODY)
+    }
+
+    pub fn with_body_for(mut self, query_value: &str, body: &str) -> Self {
+        self.overrides
+            .push((query_value.to_string(), body.to_string()));
+        self
+    }
+}
+
+impl HttpTransport for CannedTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        let body = self
+            .overrides
+            .iter()
+            .find(|(value, _)| request.query.iter().any(|(_, v)| v == value))
+            .map_or(&self.body, |(_, body)| body)
+            .as_bytes()
+            .to_vec();
+        self.requests.lock().unwrap().push(request);
+        Box::pin(async move { Ok(HttpResponse { status: 200, body }) })
+    }
+}
+
+// Records the API key sent with each request and rejects `revoked_key` with
+// an auth error.
+pub struct KeyCheckingTransport {
+    revoked_key: String,
+    pub used_keys: Arc<Mutex<Vec<String>>>,
+}
+
+impl KeyCheckingTransport {
+    pub fn new(revoked_key: &str) -> Self {
+        KeyCheckingTransport {
+            revoked_key: revoked_key.to_string(),
+            used_keys: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+}
+
+impl HttpTransport for KeyCheckingTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        let key = request.api_key.as_ref().unwrap().expose().to_string();
+        let body = if key == self.revoked_key {
+            AUTH_ERROR_BODY
+        } else {
+            OK_BODY
+        }
+        .as_bytes()
+        .to_vec();
+        self.used_keys.lock().unwrap().push(key);
+        Box::pin(async move { Ok(HttpResponse { status: 200, body }) })
+    }
+}
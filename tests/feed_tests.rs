@@ -0,0 +1,211 @@
+use futures::TryStreamExt;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+use oklink::feed::{Activity, FeedCursor};
+use oklink::transfer::TransferKind;
+use oklink::transport::{HttpRequest, HttpResponse, HttpTransport, TransportFuture};
+use oklink::Oklink;
+
+fn normal(tx_id: &str, height: u64) -> Value {
+    json!({
+        "tx_id": tx_id,
+        "method_id": "",
+        "nonce": "0",
+        "gas_price": "0",
+        "gas_limit": "21000",
+        "gas_used": "21000",
+        "block_hash": "0xBlock",
+        "height": height.to_string(),
+        "transaction_time": (height * 1000).to_string(),
+        "from": "0xAlice",
+        "to": "0xBob",
+        "is_from_contract": false,
+        "is_to_contract": false,
+        "amount": "1",
+        "symbol": "ETH",
+        "tx_fee": "0",
+        "state": "success",
+        "transaction_type": "2"
+    })
+}
+
+fn internal(tx_id: &str, height: u64) -> Value {
+    json!({
+        "tx_id": tx_id,
+        "operation": "call",
+        "block_hash": "0xBlock",
+        "height": height.to_string(),
+        "transaction_time": (height * 1000).to_string(),
+        "from": "0xContract",
+        "to": "0xAlice",
+        "is_from_contract": true,
+        "is_to_contract": false,
+        "amount": "0.1",
+        "state": "success",
+        "symbol": "ETH"
+    })
+}
+
+fn token(tx_id: &str, height: u64) -> Value {
+    json!({
+        "tx_id": tx_id,
+        "block_hash": "0xBlock",
+        "height": height.to_string(),
+        "transaction_time": (height * 1000).to_string(),
+        "from": "0xAlice",
+        "to": "0xBob",
+        "token_contract_address": "0xUsdt",
+        "token_id": "",
+        "amount": "5",
+        "symbol": "USDT",
+        "is_from_contract": false,
+        "is_to_contract": false
+    })
+}
+
+fn nft(tx_id: &str, height: u64) -> Value {
+    json!({
+        "tx_id": tx_id,
+        "block_hash": "0xBlock",
+        "height": height.to_string(),
+        "transaction_time": (height * 1000).to_string(),
+        "from": "0xAlice",
+        "to": "0xBob",
+        "token_contract_address": "0xPunks",
+        "token_id": "7",
+        "amount": "1",
+        "symbol": "PUNK",
+        "is_from_contract": false,
+        "is_to_contract": false
+    })
+}
+
+// Serves newest-first pages without blocks above `endBlockHeight`; the
+// normal list splits block 103 across its two pages. Every request is
+// recorded.
+struct HistoryTransport {
+    requests: Arc<Mutex<Vec<HttpRequest>>>,
+}
+
+impl HttpTransport for HistoryTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        let query = |key: &str| {
+            request
+                .query
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, value)| value.clone())
+        };
+        let page = query("page").unwrap_or_else(|| "1".to_string());
+        let end_height = query("endBlockHeight").map_or(u64::MAX, |h| h.parse().unwrap());
+        let protocol_type = query("protocolType").unwrap_or_default();
+        let endpoint = request.url.rsplit('/').next().unwrap_or_default();
+        let (key, total_page, list) = match (endpoint, page.as_str(), protocol_type.as_str()) {
+            ("normal-transaction-list", "1", _) => (
+                "transaction_list",
+                2,
+                vec![normal("0xA", 105), normal("0xB", 103)],
+            ),
+            ("normal-transaction-list", _, _) => (
+                "transaction_list",
+                2,
+                vec![normal("0xC", 103), normal("0xD", 100)],
+            ),
+            ("internal-transaction-list", _, _) => {
+                ("transaction_list", 1, vec![internal("0xB", 103)])
+            }
+            (_, _, "token_721") => ("transaction_lists", 1, vec![nft("0xE", 101)]),
+            (_, _, "token_1155") => ("transaction_lists", 1, vec![]),
+            (_, _, _) => (
+                "transaction_lists",
+                1,
+                vec![token("0xA", 105), token("0xE", 101)],
+            ),
+        };
+        let list: Vec<Value> = list
+            .into_iter()
+            .filter(|tx| tx["height"].as_str().unwrap().parse::<u64>().unwrap() <= end_height)
+            .collect();
+        let body = json!({
+            "code": "0",
+            "msg": "",
+            "data": {
+                "page": page,
+                "limit": "50",
+                "total_page": total_page.to_string(),
+                key: list
+            }
+        });
+        self.requests.lock().unwrap().push(request);
+        Box::pin(async move {
+            Ok(HttpResponse {
+                status: 200,
+                body: body.to_string().into_bytes(),
+            })
+        })
+    }
+}
+
+fn client() -> (Oklink, Arc<Mutex<Vec<HttpRequest>>>) {
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let transport = HistoryTransport {
+        requests: requests.clone(),
+    };
+    (Oklink::with_transport("key", transport), requests)
+}
+
+fn tx_hashes(feed: &[Activity]) -> Vec<&str> {
+    feed.iter()
+        .map(|activity| activity.tx_hash.as_str())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_activity_feed_merges_and_groups_by_transaction() {
+    let (oklink, _) = client();
+
+    let feed: Vec<Activity> = oklink
+        .activity_feed("0xAlice", None)
+        .try_collect()
+        .await
+        .unwrap();
+
+    assert_eq!(tx_hashes(&feed), vec!["0xA", "0xC", "0xB", "0xE", "0xD"]);
+    let kinds: Vec<TransferKind> = feed[0].transfers.iter().map(|t| t.kind).collect();
+    assert_eq!(kinds, vec![TransferKind::Native, TransferKind::Token]);
+    let kinds: Vec<TransferKind> = feed[2].transfers.iter().map(|t| t.kind).collect();
+    assert_eq!(kinds, vec![TransferKind::Native, TransferKind::Internal]);
+    assert_eq!(feed[2].height, 103);
+    let tokens: Vec<&str> = feed[3]
+        .transfers
+        .iter()
+        .map(|t| t.token_contract_address.as_str())
+        .collect();
+    assert_eq!(tokens, vec!["0xUsdt", "0xPunks"]);
+    assert_eq!(feed[3].transfers[1].token_id, "7");
+}
+
+#[tokio::test]
+async fn test_activity_feed_resumes_after_cursor() {
+    let (oklink, requests) = client();
+    let cursor = FeedCursor {
+        height: 103,
+        tx_hash: "0xC".to_string(),
+    };
+
+    let feed: Vec<Activity> = oklink
+        .activity_feed("0xAlice", Some(cursor))
+        .try_collect()
+        .await
+        .unwrap();
+
+    assert_eq!(tx_hashes(&feed), vec!["0xB", "0xE", "0xD"]);
+    assert_eq!(feed.last().unwrap().cursor().height, 100);
+    // Every source, tokens included, starts at the cursor's block.
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 6);
+    assert!(requests.iter().all(|request| request
+        .query
+        .contains(&("endBlockHeight".to_string(), "103".to_string()))));
+}
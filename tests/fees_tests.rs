@@ -0,0 +1,102 @@
+use oklink::fees::{fee_report, month_of};
+use oklink::oklink::TransactionDetail;
+
+fn detail(txid: &str, time: &str, fee: &str, fee_payer: Option<&str>) -> TransactionDetail {
+    serde_json::from_value(serde_json::json!({
+        "txid": txid,
+        "method_id": "",
+        "block_hash": "0xBlock",
+        "height": "100",
+        "transaction_time": time,
+        "from": "0xYourAddress",
+        "to": "0xOtherAddress",
+        "is_from_contract": false,
+        "is_to_contract": false,
+        "amount": "1",
+        "symbol": "KAIA",
+        "nonce": "1",
+        "txfee": fee,
+        "gas_price": "25",
+        "gas_limit": "21000",
+        "gas_used": "21000",
+        "state": "success",
+        "transaction_type": "0",
+        "fee_payer": fee_payer
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_month_of() {
+    assert_eq!(month_of(0), "1970-01");
+    assert_eq!(month_of(1_709_251_200_000), "2024-03");
+    assert_eq!(month_of(1_709_251_199_999), "2024-02");
+}
+
+#[test]
+fn test_fee_report_separates_delegated_fees() {
+    let details = vec![
+        detail("0x1", "1706745600000", "0.000525", None),
+        detail("0x2", "1709251200000", "0.000525", Some("0xFeePayer")),
+        detail("0x3", "1709251200001", "0.001", Some("0xYourAddress")),
+    ];
+
+    let report = fee_report("0xYourAddress", &details).unwrap();
+
+    assert_eq!(report.transaction_count, 3);
+    assert_eq!(report.total_gas_used, 63000);
+    assert_eq!(report.total_fees_paid.to_string(), "0.001525 KAIA");
+    assert_eq!(report.delegated_transaction_count, 1);
+    assert_eq!(report.delegated_fees.to_units_string(), "0.000525");
+    assert_eq!(report.months.len(), 2);
+    assert_eq!(report.months[1].month, "2024-03");
+}
+
+fn delegated(
+    txid: &str,
+    from: &str,
+    fee_payer: &str,
+    transaction_type: &str,
+    fee_ratio: Option<&str>,
+) -> TransactionDetail {
+    let mut detail = detail(txid, "1709251200000", "0.001", Some(fee_payer));
+    detail.from = from.to_string();
+    detail.transaction_type = transaction_type.to_string();
+    detail.fee_ratio = fee_ratio.map(str::to_string);
+    detail
+}
+
+#[test]
+fn test_fee_report_counts_fees_paid_as_fee_payer() {
+    let details = vec![
+        // Full delegation paid by the address for someone else.
+        delegated("0x1", "0xOther", "0xYourAddress", "0x09", None),
+        // The address pays 30% of another sender's fee.
+        delegated("0x2", "0xOther", "0xYourAddress", "0x0a", Some("30")),
+        // Someone else covers 30% of the address' own fee.
+        delegated("0x3", "0xYourAddress", "0xFeePayer", "0x0a", Some("30")),
+        // Unrelated.
+        delegated("0x4", "0xOther", "0xFeePayer", "0x09", None),
+    ];
+
+    let report = fee_report("0xYourAddress", &details).unwrap();
+
+    assert_eq!(report.transaction_count, 3);
+    assert_eq!(report.sponsored_transaction_count, 2);
+    assert_eq!(report.sponsored_fees.to_units_string(), "0.0013");
+    assert_eq!(report.delegated_fees.to_units_string(), "0.0003");
+    assert_eq!(report.total_fees_paid.to_units_string(), "0.002");
+}
+
+#[test]
+fn test_fee_report_treats_missing_ratio_as_unknown() {
+    let details = vec![delegated("0x1", "0xYourAddress", "0xFeePayer", "0x0a", None)];
+
+    let report = fee_report("0xYourAddress", &details).unwrap();
+
+    assert_eq!(report.transaction_count, 1);
+    assert_eq!(report.unknown_ratio_transaction_count, 1);
+    assert_eq!(report.delegated_transaction_count, 0);
+    assert!(report.total_fees_paid.is_zero());
+    assert!(report.delegated_fees.is_zero());
+}
@@ -0,0 +1,150 @@
+use serde_json::{json, Value};
+
+use oklink::graph::{GraphEdge, GraphNode, TraceOptions, TransferGraph};
+use oklink::transport::{HttpRequest, HttpResponse, HttpTransport, TransportFuture};
+use oklink::Oklink;
+
+#[test]
+fn test_transfer_graph_export() {
+    let graph = TransferGraph {
+        seed: "0xSeed".to_string(),
+        nodes: vec![
+            GraphNode {
+                address: "0xSeed".to_string(),
+                depth: 0,
+                label: None,
+                is_exchange: false,
+            },
+            GraphNode {
+                address: "0xHot".to_string(),
+                depth: 1,
+                label: Some("Binance \"Hot\" <1>".to_string()),
+                is_exchange: true,
+            },
+        ],
+        edges: vec![GraphEdge {
+            from: "0xSeed".to_string(),
+            to: "0xHot".to_string(),
+            tx_id: "0xTx".to_string(),
+            amount: 12.5,
+            symbol: "KAIA".to_string(),
+            token_contract_address: String::new(),
+            timestamp: 1700000000000,
+        }],
+    };
+
+    let dot = graph.to_dot();
+    assert!(dot.starts_with("digraph transfers {"));
+    assert!(dot.contains("\"0xHot\" [label=\"Binance \\\"Hot\\\" <1>\\n0xHot\", shape=box];"));
+    assert!(dot.contains(
+        "\"0xSeed\" -> \"0xHot\" [label=\"12.5 KAIA\", tooltip=\"tx 0xTx at 1700000000000\"];"
+    ));
+
+    let graphml = graph.to_graphml();
+    assert!(graphml.contains("<node id=\"0xHot\">"));
+    assert!(graphml.contains("Binance &quot;Hot&quot; &lt;1&gt;"));
+    assert!(graphml.contains("<edge source=\"0xSeed\" target=\"0xHot\">"));
+    assert!(graph.node("0xhot").unwrap().is_exchange);
+}
+
+fn transfer(tx_id: &str, to: &str, amount: &str, symbol: &str, token: &str) -> Value {
+    json!({
+        "tx_id": tx_id,
+        "method_id": "",
+        "nonce": "0",
+        "gas_price": "0",
+        "gas_limit": "0",
+        "gas_used": "0",
+        "block_hash": "",
+        "height": "1",
+        "transaction_time": "1700000000000",
+        "from": "0xSeed",
+        "to": to,
+        "is_from_contract": false,
+        "is_to_contract": false,
+        "amount": amount,
+        "symbol": symbol,
+        "tx_fee": "0",
+        "state": "success",
+        "transaction_type": "0",
+        "token_contract_address": token,
+        "token_id": ""
+    })
+}
+
+// 0xSeed's native history spans three pages; only 0xSeed has any history.
+struct TraceTransport;
+
+impl HttpTransport for TraceTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        let query = |key: &str| {
+            request
+                .query
+                .iter()
+                .find(|(k, _)| k == key)
+                .map_or(String::new(), |(_, value)| value.clone())
+        };
+        let seed = query("address") == "0xSeed";
+        let endpoint = request.url.rsplit('/').next().unwrap_or_default();
+        let data = match endpoint {
+            "normal-transaction-list" => {
+                let transaction_list = match (seed, query("page").as_str()) {
+                    (true, "1") => vec![transfer("0x1", "0xA", "10", "KAIA", "")],
+                    (true, "3") => vec![transfer("0x2", "0xExchange", "5", "KAIA", "")],
+                    (true, _) => vec![transfer("0x3", "0xDust", "0.5", "KAIA", "")],
+                    _ => vec![],
+                };
+                json!({
+                    "page": query("page"),
+                    "limit": "50",
+                    "total_page": if seed { "3" } else { "1" },
+                    "transaction_list": transaction_list
+                })
+            }
+            "token-transaction-list" => {
+                let transaction_lists = if seed {
+                    vec![
+                        transfer("0x4", "0xC", "50", "USDT", "0xUSDT"),
+                        transfer("0x5", "0xD", "200", "USDT", "0xUSDT"),
+                    ]
+                } else {
+                    vec![]
+                };
+                json!({
+                    "page": "1",
+                    "limit": "50",
+                    "total_page": "1",
+                    "transaction_lists": transaction_lists
+                })
+            }
+            _ => json!([{"label": "Binance 7", "address": "0xexchange"}]),
+        };
+        let body = json!({"code": "0", "msg": "", "data": data});
+        Box::pin(async move {
+            Ok(HttpResponse {
+                status: 200,
+                body: body.to_string().into_bytes(),
+            })
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_trace_funds_applies_per_asset_thresholds() {
+    let oklink = Oklink::with_transport("key", TraceTransport);
+    let options = TraceOptions {
+        max_depth: 1,
+        min_amount: 1.0,
+        ..TraceOptions::default()
+    }
+    .with_min_amount("0xusdt", 100.0);
+
+    let graph = oklink.trace_funds("0xSeed", &options).await.unwrap();
+
+    let addresses: Vec<&str> = graph.nodes.iter().map(|n| n.address.as_str()).collect();
+    assert_eq!(addresses, ["0xSeed", "0xA", "0xExchange", "0xD"]);
+    assert_eq!(graph.edges.len(), 3);
+    assert_eq!(graph.edges[2].token_contract_address, "0xUSDT");
+    assert!(graph.node("0xexchange").unwrap().is_exchange);
+    assert_eq!(graph.node("0xD").unwrap().depth, 1);
+}
@@ -0,0 +1,84 @@
+use mockito::mock;
+use oklink::kaia::{AccountKey, KaiaTxType};
+use oklink::Oklink;
+
+#[test]
+fn test_kaia_tx_type_parsing_and_delegation() {
+    let partial = KaiaTxType::parse("0x32").unwrap();
+    assert_eq!(
+        partial,
+        KaiaTxType::FeeDelegatedSmartContractExecutionWithRatio
+    );
+    assert!(partial.is_fee_delegated());
+    assert!(partial.has_fee_ratio());
+    assert_eq!(partial.base(), KaiaTxType::SmartContractExecution);
+
+    assert_eq!(
+        KaiaTxType::parse("9"),
+        Some(KaiaTxType::FeeDelegatedValueTransfer)
+    );
+    assert_eq!(
+        KaiaTxType::parse("TxTypeAccountUpdate"),
+        Some(KaiaTxType::AccountUpdate)
+    );
+    assert_eq!(
+        KaiaTxType::parse("0x7802"),
+        Some(KaiaTxType::EthereumDynamicFee)
+    );
+    assert_eq!(KaiaTxType::parse("1"), Some(KaiaTxType::EthereumAccessList));
+    assert_eq!(KaiaTxType::parse("2"), Some(KaiaTxType::EthereumDynamicFee));
+    assert_eq!(KaiaTxType::parse("0x2"), Some(KaiaTxType::EthereumDynamicFee));
+    assert_eq!(KaiaTxType::parse("0x7801"), Some(KaiaTxType::EthereumAccessList));
+    assert!(!KaiaTxType::EthereumDynamicFee.is_fee_delegated());
+    assert_eq!(KaiaTxType::parse("0x0b"), None);
+}
+
+#[tokio::test]
+async fn test_account_key_role_based() {
+    let _m = mock("POST", "/rpc")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "keyType": 5,
+                "key": [
+                    {"keyType": 4, "key": {"threshold": 2, "keys": [
+                        {"weight": 1, "key": {"x": "0x1", "y": "0x2"}},
+                        {"weight": 1, "key": {"x": "0x3", "y": "0x4"}}
+                    ]}},
+                    {"keyType": 2, "key": {"x": "0x5", "y": "0x6"}},
+                    {"keyType": 0, "key": {}}
+                ]
+            }
+        }"#,
+        )
+        .create();
+
+    let oklink = Oklink::new("test_api_key".to_string());
+    let rpc_url = format!("{}/rpc", mockito::server_url());
+    let key = oklink
+        .account_key("0xYourAddress", &rpc_url)
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(key.key_type(), 0x05);
+    assert!(key.is_multisig());
+    match key {
+        AccountKey::RoleBased {
+            account_update,
+            fee_payer,
+            ..
+        } => {
+            assert!(matches!(
+                account_update.as_deref(),
+                Some(AccountKey::Public(_))
+            ));
+            assert!(fee_payer.is_none());
+        }
+        other => panic!("unexpected key {:?}", other),
+    }
+}
@@ -0,0 +1,151 @@
+mod common;
+
+use std::time::Duration;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use common::{KeyCheckingTransport, OK_BODY, RATE_LIMIT_ERROR_BODY};
+use oklink::keys::{KeyOutcome, KeyPool, KeySelection, RateLimit};
+use oklink::transport::{
+    HttpRequest, HttpResponse, HttpTransport, TransportError, TransportFuture,
+};
+use oklink::Oklink;
+
+#[test]
+fn test_key_pool_rate_limits_and_rotation() {
+    let pool = KeyPool::new(KeySelection::RoundRobin)
+        .key("free", "key-a", Some(RateLimit::per_second(1)))
+        .key("pro", "key-b", Some(RateLimit::per_second(2)));
+
+    let first = pool.acquire().unwrap();
+    let second = pool.acquire().unwrap();
+    let third = pool.acquire().unwrap();
+    pool.release(&first, KeyOutcome::Success);
+    pool.release(&second, KeyOutcome::Success);
+    pool.release(&third, KeyOutcome::Success);
+
+    let retry_after = pool.acquire().err().unwrap();
+    assert!(retry_after > Duration::ZERO && retry_after <= Duration::from_secs(1));
+
+    let stats = pool.stats();
+    assert_eq!(stats[0].name, "free");
+    assert_eq!(stats[0].requests, 1);
+    assert_eq!(stats[1].requests, 2);
+    assert_eq!(stats[1].in_flight, 0);
+}
+
+#[test]
+fn test_key_outcome_classification() {
+    assert_eq!(KeyOutcome::classify(200, Some("0")), KeyOutcome::Success);
+    assert_eq!(
+        KeyOutcome::classify(200, Some("50111")),
+        KeyOutcome::AuthError
+    );
+    assert_eq!(KeyOutcome::classify(429, None), KeyOutcome::RateLimited);
+    assert_eq!(
+        KeyOutcome::classify(200, Some("50011")),
+        KeyOutcome::RateLimited
+    );
+    assert_eq!(
+        KeyOutcome::classify(200, Some("50061")),
+        KeyOutcome::QuotaError
+    );
+    assert_eq!(KeyOutcome::classify(200, Some("50001")), KeyOutcome::Error);
+}
+
+#[tokio::test]
+async fn test_failing_key_is_disabled() {
+    let pool = KeyPool::new(KeySelection::RoundRobin)
+        .key("old", "revoked", None)
+        .key("new", "valid", None);
+    let transport = KeyCheckingTransport::new("revoked");
+    let used_keys = transport.used_keys.clone();
+    let oklink = Oklink::with_transport(pool, transport);
+
+    for _ in 0..3 {
+        oklink.address_entity_labels("0xYourAddress").await.unwrap();
+    }
+
+    assert_eq!(
+        *used_keys.lock().unwrap(),
+        vec!["revoked", "valid", "valid"]
+    );
+    let stats = oklink.key_stats();
+    assert!(stats[0].disabled);
+    assert_eq!(stats[0].auth_errors, 1);
+    assert_eq!(stats[1].requests, 2);
+
+    let single = Oklink::with_transport(
+        KeyPool::new(KeySelection::LeastLoaded).key("old", "revoked", None),
+        KeyCheckingTransport::new("revoked"),
+    );
+    single.address_entity_labels("0xYourAddress").await.unwrap();
+    let error = single
+        .address_entity_labels("0xYourAddress")
+        .await
+        .unwrap_err();
+    assert!(matches!(error, TransportError::RateLimited { .. }));
+}
+
+#[tokio::test]
+async fn test_rate_limited_request_waits_for_key() {
+    let limit = RateLimit {
+        requests: 1,
+        per: Duration::from_millis(200),
+    };
+    let pool = KeyPool::new(KeySelection::RoundRobin).key("free", "valid", Some(limit));
+    let oklink = Oklink::with_transport(pool, KeyCheckingTransport::new("revoked"));
+
+    let started = std::time::Instant::now();
+    oklink.address_entity_labels("0xFirst").await.unwrap();
+    oklink.address_entity_labels("0xSecond").await.unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(150));
+    assert_eq!(oklink.key_stats()[0].requests, 2);
+
+    let impatient = Oklink::with_transport(
+        KeyPool::new(KeySelection::RoundRobin).key("free", "valid", Some(limit)),
+        KeyCheckingTransport::new("revoked"),
+    )
+    .with_max_rate_limit_wait(Duration::from_millis(50));
+    impatient.address_entity_labels("0xFirst").await.unwrap();
+    let error = impatient
+        .address_entity_labels("0xSecond")
+        .await
+        .unwrap_err();
+    assert!(matches!(error, TransportError::RateLimited { .. }));
+}
+
+// Answers the first request with HTTP 429.
+struct TooManyRequestsOnce {
+    requests: AtomicUsize,
+}
+
+impl HttpTransport for TooManyRequestsOnce {
+    fn send(&self, _request: HttpRequest) -> TransportFuture<'_> {
+        let (status, body) = if self.requests.fetch_add(1, Ordering::SeqCst) == 0 {
+            (429, RATE_LIMIT_ERROR_BODY)
+        } else {
+            (200, OK_BODY)
+        };
+        let body = body.as_bytes().to_vec();
+        Box::pin(async move { Ok(HttpResponse { status, body }) })
+    }
+}
+
+#[tokio::test]
+async fn test_single_key_client_recovers_from_a_rate_limit() {
+    let oklink = Oklink::with_transport(
+        "key",
+        TooManyRequestsOnce {
+            requests: AtomicUsize::new(0),
+        },
+    );
+
+    oklink.address_entity_labels("0xFirst").await.unwrap();
+    oklink.address_entity_labels("0xSecond").await.unwrap();
+
+    let stats = oklink.key_stats();
+    assert_eq!(stats[0].rate_limit_errors, 1);
+    assert_eq!(stats[0].requests, 2);
+    assert!(!stats[0].disabled);
+}
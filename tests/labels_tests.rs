@@ -0,0 +1,133 @@
+mod common;
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use oklink::keys::{KeyPool, KeySelection};
+use oklink::labels::{matches_keywords, HasAddresses, Labeled, EXCHANGE_KEYWORDS};
+use oklink::oklink::{BatchAddressTokenBalances, BatchTokenTransactionList, RichList};
+use oklink::transport::{
+    HttpRequest, HttpResponse, HttpTransport, TransportError, TransportFuture,
+};
+use oklink::Oklink;
+
+#[test]
+fn test_rich_list_addresses_and_labels() {
+    let rich_list: RichList = serde_json::from_value(serde_json::json!({
+        "code": "0",
+        "msg": "",
+        "data": [
+            {"symbol": "KAIA", "holder_address": "0xAbC", "amount": "100", "rank": "1"},
+            {"symbol": "KAIA", "holder_address": "0xDef", "amount": "50", "rank": "2"}
+        ]
+    }))
+    .unwrap();
+    assert_eq!(rich_list.addresses(), vec!["0xAbC", "0xDef"]);
+
+    let labeled = Labeled {
+        data: rich_list,
+        labels: HashMap::from([("0xabc".to_string(), "Binance: Hot Wallet".to_string())]),
+    };
+    assert_eq!(labeled.label("0xABC"), Some("Binance: Hot Wallet"));
+    assert_eq!(labeled.label("0xDef"), None);
+}
+
+#[test]
+fn test_exchange_keywords_match_whole_words() {
+    assert!(matches_keywords("Gate.io 3", EXCHANGE_KEYWORDS));
+    assert!(matches_keywords("OKX: Hot Wallet", EXCHANGE_KEYWORDS));
+    assert!(!matches_keywords("Delegate Pool", EXCHANGE_KEYWORDS));
+    assert!(!matches_keywords("Bridge Gateway", EXCHANGE_KEYWORDS));
+    assert!(!matches_keywords("DEX Aggregator", EXCHANGE_KEYWORDS));
+    assert!(matches_keywords("Crypto.com 2", &["crypto.com"]));
+    assert!(!matches_keywords("Crypto Fund", &["crypto.com"]));
+}
+
+#[test]
+fn test_batch_responses_list_addresses_of_every_page() {
+    let transfer = |from: &str, to: &str| {
+        serde_json::json!({
+            "tx_id": "0xTx",
+            "block_hash": "0xBlock",
+            "height": "1",
+            "transaction_time": "0",
+            "from": from,
+            "to": to,
+            "token_contract_address": "0xToken",
+            "token_id": "",
+            "amount": "1",
+            "symbol": "TKN",
+            "is_from_contract": false,
+            "is_to_contract": false
+        })
+    };
+    let page = |list: Vec<serde_json::Value>| serde_json::json!({"page": "1", "limit": "20", "total_page": "1", "transaction_list": list});
+    let transfers: BatchTokenTransactionList = serde_json::from_value(serde_json::json!({
+        "code": "0",
+        "msg": "",
+        "data": [page(vec![transfer("0xA", "0xB")]), page(vec![transfer("0xC", "0xD")])]
+    }))
+    .unwrap();
+    assert_eq!(transfers.addresses(), vec!["0xA", "0xB", "0xC", "0xD"]);
+
+    let balances: BatchAddressTokenBalances = serde_json::from_value(serde_json::json!({
+        "code": "0",
+        "msg": "",
+        "data": {
+            "page": "1",
+            "limit": "20",
+            "total_page": "1",
+            "balance_list": [{
+                "address": "0xHolder",
+                "holding_amount": "1",
+                "token_contract_address": "0xToken",
+                "token_id": ""
+            }]
+        }
+    }))
+    .unwrap();
+    assert_eq!(balances.addresses(), vec!["0xHolder", "0xToken"]);
+}
+
+// Answers the first request with a rate limit error and later ones with a label.
+struct FlakyLabelTransport {
+    requests: Arc<AtomicUsize>,
+}
+
+impl HttpTransport for FlakyLabelTransport {
+    fn send(&self, _request: HttpRequest) -> TransportFuture<'_> {
+        let body = if self.requests.fetch_add(1, Ordering::SeqCst) == 0 {
+            common::RATE_LIMIT_ERROR_BODY
+        } else {
+            r#"{"code": "0", "msg": "", "data": [{"label": "Binance 7", "address": "0xhot"}]}"#
+        }
+        .as_bytes()
+        .to_vec();
+        Box::pin(async move { Ok(HttpResponse { status: 200, body }) })
+    }
+}
+
+#[tokio::test]
+async fn test_entity_labels_does_not_cache_error_responses() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    // Skips the short cooldown the rate limit error puts the only key on.
+    let pool = KeyPool::new(KeySelection::RoundRobin)
+        .with_cooldown(Duration::ZERO)
+        .key("default", "key", None);
+    let oklink = Oklink::with_transport(
+        pool,
+        FlakyLabelTransport {
+            requests: requests.clone(),
+        },
+    );
+
+    let error = oklink.entity_labels(&["0xHot"]).await.unwrap_err();
+    assert!(matches!(error, TransportError::Api { ref code, .. } if code == "50011"));
+
+    let labels = oklink.entity_labels(&["0xHot"]).await.unwrap();
+    assert_eq!(labels["0xhot"], "Binance 7");
+    oklink.entity_labels(&["0xHot"]).await.unwrap();
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+}
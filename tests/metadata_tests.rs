@@ -0,0 +1,97 @@
+#![cfg(feature = "nft-metadata")]
+
+use oklink::metadata::{token_id_hex, MetadataError, MetadataResolver};
+use mockito::mock;
+
+#[tokio::test]
+async fn test_resolve_http_metadata() {
+    let _m = mock("GET", "/metadata/1.json")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{
+            "name": "Kaia Cat #1",
+            "image": "ipfs://QmImage/1.png",
+            "attributes": [{"trait_type": "Fur", "value": "Orange"}]
+        }"#)
+        .expect(1)
+        .create();
+
+    let resolver = MetadataResolver::new();
+    let uri = format!("{}/metadata/1.json", mockito::server_url());
+    let metadata = resolver.resolve(&uri).await.unwrap();
+    // The second call is served from the cache.
+    resolver.resolve(&uri).await.unwrap();
+
+    assert_eq!(metadata.name.as_deref(), Some("Kaia Cat #1"));
+    assert_eq!(metadata.attributes[0].trait_type.as_deref(), Some("Fur"));
+    _m.assert();
+}
+
+#[tokio::test]
+async fn test_resolve_ipfs_falls_back_to_next_gateway() {
+    let _bad = mock("GET", "/bad-gateway/QmMeta/2.json").with_status(502).create();
+    let _good = mock("GET", "/ipfs/QmMeta/2.json")
+        .with_status(200)
+        .with_body(r#"{"name": "Kaia Cat #2"}"#)
+        .create();
+
+    let resolver = MetadataResolver::new().with_ipfs_gateways(vec![
+        format!("{}/bad-gateway/", mockito::server_url()),
+        format!("{}/ipfs/", mockito::server_url()),
+    ]);
+    let metadata = resolver.resolve("ipfs://QmMeta/2.json").await.unwrap();
+
+    assert_eq!(metadata.name.as_deref(), Some("Kaia Cat #2"));
+}
+
+#[tokio::test]
+async fn test_resolve_data_uri_and_size_limit() {
+    let resolver = MetadataResolver::new().with_max_bytes(64);
+    let metadata = resolver
+        .resolve("data:application/json;base64,eyJuYW1lIjoiT25jaGFpbiJ9")
+        .await
+        .unwrap();
+    assert_eq!(metadata.name.as_deref(), Some("Onchain"));
+
+    let _m = mock("GET", "/metadata/large.json")
+        .with_status(200)
+        .with_body(format!(r#"{{"name": "{}"}}"#, "x".repeat(128)))
+        .create();
+    let uri = format!("{}/metadata/large.json", mockito::server_url());
+    assert!(matches!(resolver.resolve(&uri).await, Err(MetadataError::TooLarge(_))));
+}
+
+#[test]
+fn test_token_id_hex_covers_uint256() {
+    assert_eq!(token_id_hex("1").unwrap(), format!("{:0>64}", "1"));
+    // 2^128 no longer fits in a u128.
+    assert_eq!(
+        token_id_hex("340282366920938463463374607431768211456").unwrap(),
+        format!("{:0>64}", format!("1{}", "0".repeat(32)))
+    );
+    let max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+    assert_eq!(token_id_hex(max).unwrap(), "f".repeat(64));
+    assert_eq!(token_id_hex(&format!("{}6", &max[..max.len() - 1])), None);
+    assert_eq!(token_id_hex("0xABC").unwrap(), format!("{:0>64}", "abc"));
+    assert_eq!(token_id_hex("12a"), None);
+}
+
+#[tokio::test]
+async fn test_resolve_token_substitutes_large_ids() {
+    let id = "f".repeat(64);
+    let _m = mock("GET", format!("/kip37/{}.json", id).as_str())
+        .with_status(200)
+        .with_body(r#"{"name": "Max"}"#)
+        .create();
+
+    let resolver = MetadataResolver::new();
+    let uri = format!("{}/kip37/{{id}}.json", mockito::server_url());
+    let max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+    let metadata = resolver.resolve_token(&uri, max).await.unwrap();
+
+    assert_eq!(metadata.name.as_deref(), Some("Max"));
+    assert!(matches!(
+        resolver.resolve_token(&uri, "not-an-id").await,
+        Err(MetadataError::InvalidTokenId(_))
+    ));
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use common::CannedTransport;
+use oklink::middleware::{HeaderInjector, Metrics, Middleware, RequestLogger};
+use oklink::transport::HttpRequest;
+use oklink::Oklink;
+
+struct ProxyRewrite;
+
+impl Middleware for ProxyRewrite {
+    fn before_request(&self, request: &mut HttpRequest) {
+        request.url = request
+            .url
+            .replace("https://www.oklink.com/", "http://egress.internal/oklink/");
+    }
+}
+
+#[tokio::test]
+async fn test_middleware_chain() {
+    let transport = CannedTransport::ok();
+    let requests = transport.requests.clone();
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let sink = lines.clone();
+    let metrics = Metrics::new();
+    let oklink = Oklink::with_transport("test_api_key".to_string(), transport)
+        .with_middleware(HeaderInjector::new().header("traceparent", "00-abc-def-01"))
+        .with_middleware(ProxyRewrite)
+        .with_middleware(metrics.clone())
+        .with_middleware(RequestLogger::new().with_sink(move |line| {
+            sink.lock().unwrap().push(line.to_string());
+        }));
+
+    oklink.address_entity_labels("0xYourAddress").await.unwrap();
+    oklink
+        .address_entity_labels("0xOtherAddress")
+        .await
+        .unwrap();
+
+    let requests = requests.lock().unwrap();
+    assert_eq!(
+        requests[0].url,
+        "http://egress.internal/oklink/api/v5/explorer/address/entity-labels"
+    );
+    assert!(requests[0]
+        .headers
+        .contains(&("traceparent".to_string(), "00-abc-def-01".to_string())));
+
+    let snapshot = metrics.snapshot();
+    let labels = &snapshot["/oklink/api/v5/explorer/address/entity-labels"];
+    assert_eq!(labels.requests, 2);
+    assert_eq!(labels.errors, 0);
+    assert_eq!(metrics.total().requests, 2);
+
+    let lines = lines.lock().unwrap();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("GET http://egress.internal/oklink/"));
+    assert!(lines[0].contains("address=0xYourAddress"));
+    assert!(!lines[0].contains("test_api_key"));
+}
@@ -0,0 +1,97 @@
+use serde_json::json;
+
+use oklink::nft::{ownership_chain, NftError, NftHolding, NftTransfer};
+use oklink::oklink::NftTransaction;
+use oklink::transport::{HttpRequest, HttpResponse, HttpTransport, TransportFuture};
+use oklink::types::ProtocolType;
+use oklink::Oklink;
+
+fn transaction(protocol_type: &str, to: &str, amount: &str) -> NftTransaction {
+    serde_json::from_value(json!({
+        "txid": "0xTx",
+        "block_hash": "0xBlock",
+        "height": "100",
+        "transaction_time": "1700000000000",
+        "from": "0xMinter",
+        "to": to,
+        "token_contract_address": "0xNft",
+        "token_id": "1",
+        "amount": amount,
+        "protocol_type": protocol_type,
+        "method_id": "",
+        "state": "success"
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_ownership_chain_includes_kip37_recipients() {
+    let history: Vec<NftTransfer> = [
+        transaction("token_1155", "0xAlice", "5"),
+        transaction("token_1155", "0xAlice", "1"),
+        transaction("token_1155", "0xBob", "2"),
+    ]
+    .iter()
+    .map(|tx| NftTransfer::from_transaction(tx).unwrap())
+    .collect();
+
+    assert_eq!(ownership_chain(&history), vec!["0xAlice", "0xBob"]);
+}
+
+#[test]
+fn test_invalid_kip37_amount_is_an_error() {
+    let result = NftTransfer::from_transaction(&transaction("token_1155", "0xAlice", "1.5"));
+
+    assert!(matches!(result, Err(NftError::InvalidAmount(amount)) if amount == "1.5"));
+    assert!(NftTransfer::from_transaction(&transaction("token_721", "0xAlice", "")).is_ok());
+}
+
+// One KIP-37 balance from `address-balance-fills`.
+struct InventoryTransport;
+
+impl HttpTransport for InventoryTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        assert!(request.url.ends_with("address/address-balance-fills"));
+        let body = json!({
+            "code": "0",
+            "msg": "",
+            "data": {
+                "page": "1",
+                "limit": "50",
+                "total_page": "1",
+                "token_list": [{
+                    "symbol": "ITEM",
+                    "token_contract_address": "0xNft",
+                    "token_type": "KIP37",
+                    "holding_amount": "3",
+                    "token_id": "9"
+                }]
+            }
+        });
+        Box::pin(async move {
+            Ok(HttpResponse {
+                status: 200,
+                body: body.to_string().into_bytes(),
+            })
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_nft_holdings_reads_balance_details() {
+    let oklink = Oklink::with_transport("key", InventoryTransport);
+
+    let holdings = oklink
+        .nft_holdings("0xAlice", ProtocolType::Token1155, None)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        holdings,
+        vec![NftHolding::Kip37 {
+            token_contract_address: "0xNft".to_string(),
+            token_id: "9".to_string(),
+            amount: 3,
+        }]
+    );
+}
@@ -0,0 +1,236 @@
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use oklink::transport::{HttpRequest, HttpResponse, HttpTransport, TransportFuture};
+use oklink::Oklink;
+
+const WRAPPED_KAIA: &str = "0x19aac5f612f524b754ca7e7c41cbfa2e981a4432";
+
+fn query<'a>(request: &'a HttpRequest, key: &str) -> &'a str {
+    request
+        .query
+        .iter()
+        .find(|(k, _)| k == key)
+        .map_or("", |(_, value)| value.as_str())
+}
+
+fn address_summary() -> Value {
+    let mut data = serde_json::Map::new();
+    for field in [
+        "chain_full_name",
+        "chain_short_name",
+        "address",
+        "contract_address",
+        "transaction_count",
+        "verifying",
+        "send_amount",
+        "receive_amount",
+        "token_amount",
+        "total_token_value",
+        "create_contract_address",
+        "create_contract_transaction_hash",
+        "first_transaction_time",
+        "last_transaction_time",
+        "token",
+        "bandwidth",
+        "energy",
+        "voting_rights",
+        "unclaimed_voting_rewards",
+    ] {
+        data.insert(field.to_string(), json!(""));
+    }
+    data.insert("balance".to_string(), json!("10"));
+    data.insert("balance_symbol".to_string(), json!("KAIA"));
+    data.insert("is_aa_address".to_string(), json!(false));
+    Value::Object(data)
+}
+
+fn balance(token: &str, symbol: &str, amount: &str, value_usd: &str, token_id: &str) -> Value {
+    json!({
+        "symbol": symbol,
+        "token_contract_address": token,
+        "holding_amount": amount,
+        "price_usd": "1",
+        "value_usd": value_usd,
+        "token_id": token_id
+    })
+}
+
+fn token_info(token: &str, symbol: &str, precision: &str) -> Value {
+    json!({
+        "token_full_name": symbol,
+        "token": symbol,
+        "precision": precision,
+        "token_contract_address": token,
+        "protocol_type": "token_20",
+        "address_count": "0",
+        "total_supply": "0",
+        "circulating_supply": "0",
+        "price": "1",
+        "website": "",
+        "total_market_cap": "0",
+        "issue_date": "",
+        "transaction_amount_24h": "0",
+        "tvl": "0",
+        "logo_url": ""
+    })
+}
+
+fn token_transfer(token: &str, height: u64) -> Value {
+    json!({
+        "tx_id": format!("0x{}", height),
+        "block_hash": "0xBlock",
+        "height": height.to_string(),
+        "transaction_time": (height * 1000).to_string(),
+        "from": "0xAlice",
+        "to": "0xBob",
+        "token_contract_address": token,
+        "token_id": "",
+        "amount": "5",
+        "symbol": "TKN",
+        "is_from_contract": false,
+        "is_to_contract": false
+    })
+}
+
+// Holds 10 KAIA, 100 USDT and one NFT; counts `token_list` lookups.
+#[derive(Default)]
+struct PortfolioTransport {
+    token_lookups: AtomicUsize,
+}
+
+impl HttpTransport for PortfolioTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        let endpoint = request.url.rsplit('/').next().unwrap_or_default();
+        let data = match endpoint {
+            "address-summary" => address_summary(),
+            "token-balance" => {
+                let token_list = match query(&request, "protocolType") {
+                    "token_20" => vec![balance("0xUsdt", "USDT", "100", "100", "")],
+                    "token_721" => vec![balance("0xPunk", "PUNK", "1", "", "7")],
+                    _ => vec![],
+                };
+                json!({"page": "1", "limit": "50", "total_page": "1", "token_list": token_list})
+            }
+            "token-list" => {
+                self.token_lookups.fetch_add(1, Ordering::SeqCst);
+                json!({
+                    "page": "1",
+                    "limit": "20",
+                    "total_page": "1",
+                    "token_list": [token_info("0xUsdt", "USDT", "6")]
+                })
+            }
+            "price-multi" => json!([{
+                "chain_id": "8217",
+                "token_contract_address": WRAPPED_KAIA,
+                "last_price": "0.5",
+                "price_change_24h": "0"
+            }]),
+            // 0xOld was sold before block 100; 0xLate arrived after it.
+            "token-transaction-list" => {
+                assert_eq!(query(&request, "endBlockHeight"), "100");
+                json!({
+                    "page": "1",
+                    "limit": "50",
+                    "total_page": "1",
+                    "transaction_lists": [
+                        token_transfer("0xLate", 200),
+                        token_transfer("0xUsdt", 95),
+                        token_transfer("0xOld", 90)
+                    ]
+                })
+            }
+            "address-balance-history" => {
+                let token = query(&request, "tokenContractAddress");
+                json!({
+                    "address": "0xAlice",
+                    "height": "100",
+                    "balance": if token.is_empty() { "3" } else { "50" },
+                    "balance_symbol": if token.is_empty() { "KAIA" } else { "TKN" },
+                    "token_contract_address": token,
+                    "block_time": "100000"
+                })
+            }
+            "supply-history" => json!({
+                "height": "100",
+                "total_supply": "1000",
+                "block_time": "100000"
+            }),
+            other => panic!("unexpected endpoint {}", other),
+        };
+        let body = json!({"code": "0", "msg": "", "data": data});
+        Box::pin(async move {
+            Ok(HttpResponse {
+                status: 200,
+                body: body.to_string().into_bytes(),
+            })
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_portfolio_values_native_balance_through_wrapped_kaia() {
+    let oklink = Oklink::with_transport("key", PortfolioTransport::default());
+
+    let portfolio = oklink.portfolio("0xAlice").await.unwrap();
+
+    assert_eq!(portfolio.native.price_usd, Some(0.5));
+    assert_eq!(portfolio.native.value_usd, Some(5.0));
+    assert_eq!(portfolio.fungible[0].decimals, Some(6));
+    assert_eq!(portfolio.nfts[0].token_ids, vec!["7"]);
+    assert_eq!(portfolio.total_value_usd(), 105.0);
+}
+
+#[tokio::test]
+async fn test_portfolio_reuses_cached_token_meta() {
+    let transport = std::sync::Arc::new(PortfolioTransport::default());
+    let oklink = Oklink::with_transport("key", SharedTransport(transport.clone()));
+
+    oklink.portfolio("0xAlice").await.unwrap();
+    oklink.portfolio("0xAlice").await.unwrap();
+
+    assert_eq!(transport.token_lookups.load(Ordering::SeqCst), 1);
+}
+
+struct SharedTransport(std::sync::Arc<PortfolioTransport>);
+
+impl HttpTransport for SharedTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        self.0.send(request)
+    }
+}
+
+#[tokio::test]
+async fn test_portfolio_at_includes_tokens_sold_before_the_height() {
+    let oklink = Oklink::with_transport("key", PortfolioTransport::default());
+
+    let portfolio = oklink.portfolio_at("0xAlice", "100").await.unwrap();
+
+    let tokens: Vec<&str> = portfolio
+        .tokens
+        .iter()
+        .map(|t| t.token_contract_address.as_str())
+        .collect();
+    assert_eq!(tokens, vec!["0xold", "0xusdt"]);
+    assert_eq!(portfolio.native.balance, "3");
+    assert_eq!(portfolio.tokens[0].share_of_supply, Some(0.05));
+}
+
+#[tokio::test]
+async fn test_portfolio_at_with_tokens_uses_the_given_set() {
+    let oklink = Oklink::with_transport("key", PortfolioTransport::default());
+
+    let portfolio = oklink
+        .portfolio_at_with_tokens("0xAlice", "100", &["0xB", "0xA", "0xB"])
+        .await
+        .unwrap();
+
+    let tokens: Vec<&str> = portfolio
+        .tokens
+        .iter()
+        .map(|t| t.token_contract_address.as_str())
+        .collect();
+    assert_eq!(tokens, vec!["0xA", "0xB"]);
+    assert_eq!(portfolio.height, "100");
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "prometheus")]
+
+mod common;
+
+use std::time::Duration;
+
+use common::{CannedTransport, RATE_LIMIT_ERROR_BODY};
+use oklink::keys::{KeyPool, KeySelection, RateLimit};
+use oklink::prometheus::PrometheusMetrics;
+use oklink::Oklink;
+
+#[tokio::test]
+async fn test_prometheus_metrics() {
+    let metrics = PrometheusMetrics::new();
+    let transport = CannedTransport::ok().with_body_for("0xBad", RATE_LIMIT_ERROR_BODY);
+    let oklink = Oklink::with_transport("test_api_key".to_string(), transport)
+        .with_middleware(metrics.clone());
+
+    oklink.address_entity_labels("0xGood").await.unwrap();
+    oklink.address_entity_labels("0xBad").await.unwrap();
+
+    let text = metrics.render();
+    let endpoint = "endpoint=\"/api/v5/explorer/address/entity-labels\"";
+    assert!(text.contains(&format!("oklink_requests_total{{{}}} 2", endpoint)));
+    assert!(text.contains(&format!(
+        "oklink_errors_total{{{},code=\"50011\"}} 1",
+        endpoint
+    )));
+    assert!(text.contains(&format!(
+        "oklink_request_duration_seconds_bucket{{{},le=\"+Inf\"}} 2",
+        endpoint
+    )));
+    assert!(text.contains("# TYPE oklink_request_duration_seconds histogram"));
+    assert!(text.contains(&format!(
+        "oklink_rate_limit_wait_seconds_count{{{}}} 2",
+        endpoint
+    )));
+}
+
+#[tokio::test]
+async fn test_prometheus_records_rate_limit_wait() {
+    let metrics = PrometheusMetrics::new();
+    let limit = RateLimit {
+        requests: 1,
+        per: Duration::from_millis(100),
+    };
+    let pool = KeyPool::new(KeySelection::RoundRobin).key("free", "test_api_key", Some(limit));
+    let oklink =
+        Oklink::with_transport(pool, CannedTransport::ok()).with_middleware(metrics.clone());
+
+    oklink.address_entity_labels("0xFirst").await.unwrap();
+    oklink.address_entity_labels("0xSecond").await.unwrap();
+
+    let text = metrics.render();
+    let endpoint = "endpoint=\"/api/v5/explorer/address/entity-labels\"";
+    assert!(text.contains("# TYPE oklink_rate_limit_wait_seconds histogram"));
+    // The first request found the key free; the second waited for it.
+    assert!(text.contains(&format!(
+        "oklink_rate_limit_wait_seconds_bucket{{{},le=\"0.05\"}} 1",
+        endpoint
+    )));
+    assert!(text.contains(&format!(
+        "oklink_rate_limit_wait_seconds_count{{{}}} 2",
+        endpoint
+    )));
+}
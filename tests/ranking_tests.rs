@@ -0,0 +1,79 @@
+use oklink::ranking::{RankingEntry, RankingKind, RankingSnapshot, SnapshotError};
+
+fn snapshot(taken_at: u64, entries: &[(u32, &str, &str)]) -> RankingSnapshot {
+    RankingSnapshot {
+        kind: RankingKind::RichList,
+        symbol: "KAIA".to_string(),
+        taken_at,
+        entries: entries
+            .iter()
+            .map(|(rank, address, amount)| RankingEntry {
+                rank: *rank,
+                address: address.to_string(),
+                amount: amount.to_string(),
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn test_ranking_diff() {
+    let last_week = snapshot(
+        1,
+        &[
+            (1, "0xA", "500"),
+            (2, "0xB", "400.000000000000000001"),
+            (3, "0xC", "100"),
+        ],
+    );
+    let this_week = snapshot(
+        2,
+        &[(1, "0xb", "700"), (2, "0xA", "500.0"), (3, "0xD", "200")],
+    );
+
+    let diff = last_week.diff(&this_week).unwrap();
+
+    assert_eq!(diff.entrants.len(), 1);
+    assert_eq!(diff.entrants[0].address, "0xD");
+    assert_eq!(diff.exits.len(), 1);
+    assert_eq!(diff.exits[0].address, "0xC");
+    assert_eq!(diff.changes.len(), 2);
+    assert_eq!(diff.changes[0].address, "0xb");
+    assert_eq!(diff.changes[0].rank_delta(), 1);
+    assert_eq!(diff.changes[0].amount_delta, "299.999999999999999999");
+    assert_eq!(diff.changes[1].rank_delta(), -1);
+    assert_eq!(diff.changes[1].amount_delta, "0");
+}
+
+#[test]
+fn test_ranking_snapshot_roundtrip() {
+    let path = std::env::temp_dir().join("oklink_ranking_snapshot_test.json");
+    let original = snapshot(1, &[(1, "0xA", "500.5")]);
+
+    original.save(&path).unwrap();
+    let loaded = RankingSnapshot::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded, original);
+}
+
+#[test]
+fn test_ranking_diff_rejects_different_rankings() {
+    let rich_list = snapshot(1, &[(1, "0xA", "500")]);
+    let mut ranking = snapshot(2, &[(1, "0xA", "400")]);
+
+    let diff = rich_list.diff(&ranking).unwrap();
+    assert_eq!(diff.changes[0].amount_delta, "-100");
+
+    ranking.kind = RankingKind::NativeTokenRanking;
+    assert!(matches!(
+        rich_list.diff(&ranking),
+        Err(SnapshotError::Mismatch { .. })
+    ));
+    ranking.kind = RankingKind::RichList;
+    ranking.symbol = "USDT".to_string();
+    assert!(matches!(
+        rich_list.diff(&ranking),
+        Err(SnapshotError::Mismatch { .. })
+    ));
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use common::KeyCheckingTransport;
+use oklink::keys::{KeyOutcome, KeyPool, KeySelection};
+use oklink::secret::{ApiKey, SecretError};
+use oklink::transport::{HttpMethod, HttpRequest};
+use oklink::Oklink;
+
+#[test]
+fn test_api_key_is_redacted() {
+    let key = ApiKey::new("super-secret");
+    assert_eq!(format!("{:?}", key), "ApiKey(***)");
+    assert_eq!(key.to_string(), "***");
+    assert_eq!(key.expose(), "super-secret");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://www.oklink.com/api/v5/explorer/blockchain/summary".to_string(),
+        headers: vec![("Authorization".to_string(), key.expose().to_string())],
+        api_key: Some(key.clone()),
+        query: vec![("chainShortName".to_string(), "eth".to_string())],
+        body: None,
+    };
+    let printed = format!("{:?}", request);
+    assert!(!printed.contains("super-secret"));
+    assert!(printed.contains("chainShortName"));
+}
+
+#[test]
+fn test_api_key_from_env_and_file() {
+    std::env::set_var("OKLINK_SECRET_TEST_KEY", "env-key");
+    let key = ApiKey::from_env("OKLINK_SECRET_TEST_KEY").unwrap();
+    assert_eq!(key.expose(), "env-key");
+    assert!(matches!(
+        ApiKey::from_env("OKLINK_SECRET_TEST_MISSING"),
+        Err(SecretError::Env(_))
+    ));
+
+    let path = std::env::temp_dir().join("oklink_secret_test_key");
+    std::fs::write(&path, "  file-key\n").unwrap();
+    assert_eq!(ApiKey::from_file(&path).unwrap().expose(), "file-key");
+    std::fs::write(&path, "\n").unwrap();
+    assert!(matches!(ApiKey::from_file(&path), Err(SecretError::Empty)));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_provider_key_is_refetched_after_auth_error() {
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let provider_fetches = fetches.clone();
+    let pool = KeyPool::new(KeySelection::RoundRobin)
+        .with_cooldown(Duration::ZERO)
+        .provider(
+            "vault",
+            move || match provider_fetches.fetch_add(1, Ordering::SeqCst) {
+                0 => Ok(ApiKey::new("rotated-out")),
+                _ => Ok(ApiKey::new("current")),
+            },
+            None,
+        );
+    let transport = KeyCheckingTransport::new("rotated-out");
+    let used_keys = transport.used_keys.clone();
+    let oklink = Oklink::with_transport(pool, transport);
+
+    for _ in 0..3 {
+        oklink.address_entity_labels("0xYourAddress").await.unwrap();
+    }
+
+    assert_eq!(
+        *used_keys.lock().unwrap(),
+        vec!["rotated-out", "current", "current"]
+    );
+    assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    assert_eq!(oklink.key_stats()[0].auth_errors, 1);
+}
+
+#[test]
+fn test_failing_provider_disables_key_until_refresh() {
+    let available = Arc::new(Mutex::new(false));
+    let provider_available = available.clone();
+    let pool = KeyPool::new(KeySelection::RoundRobin).provider(
+        "vault",
+        move || {
+            if *provider_available.lock().unwrap() {
+                Ok(ApiKey::new("current"))
+            } else {
+                Err(SecretError::Provider("vault sealed".to_string()))
+            }
+        },
+        None,
+    );
+
+    assert!(pool.acquire().is_err());
+    assert!(pool.stats()[0].disabled);
+
+    *available.lock().unwrap() = true;
+    pool.refresh().unwrap();
+    assert!(pool.acquire().is_ok());
+    assert!(!pool.stats()[0].disabled);
+}
+
+#[test]
+fn test_refresh_reports_every_failing_provider() {
+    let pool = KeyPool::new(KeySelection::RoundRobin)
+        .provider(
+            "sealed",
+            || Err(SecretError::Provider("vault sealed".to_string())),
+            None,
+        )
+        .provider("vault", || Ok(ApiKey::new("current")), None)
+        .provider("missing", || Err(SecretError::Empty), None);
+
+    let errors = pool.refresh().unwrap_err();
+
+    let names: Vec<&str> = errors.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["sealed", "missing"]);
+    assert!(matches!(errors[1].1, SecretError::Empty));
+    let lease = pool.acquire().unwrap();
+    assert_eq!(pool.stats()[1].requests, 1);
+    pool.release(&lease, KeyOutcome::Success);
+}
+
+#[test]
+fn test_provider_runs_without_the_pool_lock() {
+    let slot: Arc<Mutex<Option<Arc<KeyPool>>>> = Arc::new(Mutex::new(None));
+    let provider_slot = slot.clone();
+    let pool = Arc::new(KeyPool::new(KeySelection::RoundRobin).provider(
+        "vault",
+        move || {
+            // Reading the stats takes the pool's lock.
+            let pool = provider_slot.lock().unwrap().clone().unwrap();
+            assert_eq!(pool.stats().len(), 1);
+            Ok(ApiKey::new("current"))
+        },
+        None,
+    ));
+    *slot.lock().unwrap() = Some(pool.clone());
+
+    assert!(pool.acquire().is_ok());
+    pool.refresh().unwrap();
+    slot.lock().unwrap().take();
+}
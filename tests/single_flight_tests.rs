@@ -0,0 +1,108 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use oklink::transport::{
+    HttpRequest, HttpResponse, HttpTransport, TransportError, TransportFuture,
+};
+use oklink::Oklink;
+
+struct SlowTransport {
+    requests: Arc<AtomicUsize>,
+    fail: bool,
+}
+
+impl HttpTransport for SlowTransport {
+    fn send(&self, _request: HttpRequest) -> TransportFuture<'_> {
+        self.requests.fetch_add(1, Ordering::SeqCst);
+        let fail = self.fail;
+        Box::pin(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            if fail {
+                return Err(TransportError::other("connection reset"));
+            }
+            Ok(HttpResponse {
+                status: 200,
+                body: br#"{"code": "0", "msg": "", "data": []}"#.to_vec(),
+            })
+        })
+    }
+}
+
+fn client(fail: bool) -> (Oklink, Arc<AtomicUsize>) {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let transport = SlowTransport {
+        requests: requests.clone(),
+        fail,
+    };
+    (Oklink::with_transport("key", transport), requests)
+}
+
+#[tokio::test]
+async fn test_concurrent_identical_requests_are_coalesced() {
+    let (oklink, requests) = client(false);
+
+    let (a, b, c, other) = futures::join!(
+        oklink.address_entity_labels("0xabc"),
+        oklink.address_entity_labels("0xabc"),
+        oklink.address_entity_labels("0xabc"),
+        oklink.address_entity_labels("0xdef"),
+    );
+    assert!(a.is_ok() && b.is_ok() && c.is_ok() && other.is_ok());
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+    // Once the first call has finished, the next one goes out again.
+    oklink.address_entity_labels("0xabc").await.unwrap();
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_coalesced_errors_reach_every_caller() {
+    let (oklink, requests) = client(true);
+
+    let (a, b) = futures::join!(
+        oklink.address_entity_labels("0xabc"),
+        oklink.address_entity_labels("0xabc"),
+    );
+    for result in [a, b] {
+        match result {
+            Err(TransportError::Other(error)) => assert_eq!(error.to_string(), "connection reset"),
+            other => panic!("expected the transport's error, got {:?}", other),
+        }
+    }
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_single_flight_can_be_disabled() {
+    let (oklink, requests) = client(false);
+    let oklink = oklink.with_single_flight(false);
+
+    let (a, b) = futures::join!(
+        oklink.address_entity_labels("0xabc"),
+        oklink.address_entity_labels("0xabc"),
+    );
+    assert!(a.is_ok() && b.is_ok());
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_query_values_with_separators_are_not_coalesced() {
+    let (oklink, requests) = client(false);
+
+    // Joined naively, both queries read
+    // "protocolType=ERC20&tokenContractAddress=0xabc".
+    let _ = futures::join!(
+        oklink.token_list(
+            Some("ERC20&tokenContractAddress=0xabc"),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ),
+        oklink.token_list(Some("ERC20"), Some("0xabc"), None, None, None, None, None),
+    );
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,133 @@
+use oklink::oklink::{
+    BatchInternalTransactionList, NormalTransactionList, TokenTransaction, TokenTransactionList,
+};
+use oklink::transfer::{Transfer, TransferKind, TransferStatus};
+use serde_json::json;
+
+#[test]
+fn test_normal_transactions_become_native_transfers() {
+    let list: NormalTransactionList = serde_json::from_value(json!({
+        "code": "0",
+        "msg": "",
+        "data": {
+            "page": "1",
+            "limit": "20",
+            "total_page": "1",
+            "transaction_list": [{
+                "tx_id": "0xTx1",
+                "method_id": "",
+                "nonce": "7",
+                "gas_price": "1000000000",
+                "gas_limit": "21000",
+                "gas_used": "21000",
+                "block_hash": "0xBlock",
+                "height": "18000000",
+                "transaction_time": "1700000000000",
+                "from": "0xAlice",
+                "to": "0xBob",
+                "is_from_contract": false,
+                "is_to_contract": false,
+                "amount": "1.5",
+                "symbol": "ETH",
+                "tx_fee": "0.000021",
+                "state": "fail",
+                "transaction_type": "2"
+            }]
+        }
+    }))
+    .unwrap();
+
+    let transfers = list.transfers();
+    assert_eq!(transfers.len(), 1);
+    let transfer = &transfers[0];
+    assert_eq!(transfer.kind, TransferKind::Native);
+    assert_eq!(transfer.asset, "ETH");
+    assert_eq!(transfer.tx_hash, "0xTx1");
+    assert_eq!(transfer.height, Some(18_000_000));
+    assert_eq!(transfer.time, Some(1_700_000_000_000));
+    assert_eq!(transfer.status, TransferStatus::Failed);
+    assert!(transfer.involves("0xbob"));
+}
+
+#[test]
+fn test_batch_internal_transactions_are_flattened() {
+    let list: BatchInternalTransactionList = serde_json::from_value(json!({
+        "code": "0",
+        "msg": "",
+        "data": [{
+            "page": "1",
+            "limit": "20",
+            "total_page": "1",
+            "transaction_list": [{
+                "tx_id": "0xTx2",
+                "block_hash": "0xBlock",
+                "height": "18000001",
+                "transaction_time": "1700000012000",
+                "operation": "call",
+                "from": "0xContract",
+                "to": "0xAlice",
+                "is_from_contract": true,
+                "is_to_contract": false,
+                "amount": "0.2"
+            }]
+        }, {
+            "page": "1",
+            "limit": "20",
+            "total_page": "1",
+            "transaction_list": []
+        }]
+    }))
+    .unwrap();
+
+    let transfers = list.transfers();
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].kind, TransferKind::Internal);
+    assert_eq!(transfers[0].status, TransferStatus::Unknown);
+    assert_eq!(transfers[0].amount, "0.2");
+}
+
+#[test]
+fn test_token_transactions_keep_the_token() {
+    let tx = TokenTransaction {
+        tx_id: "0xTx3".to_string(),
+        block_hash: "0xBlock".to_string(),
+        height: "18000002".to_string(),
+        transaction_time: "1700000024000".to_string(),
+        from: "0xAlice".to_string(),
+        to: "0xBob".to_string(),
+        token_contract_address: "0xUsdt".to_string(),
+        token_id: "".to_string(),
+        amount: "250".to_string(),
+        symbol: "USDT".to_string(),
+        is_from_contract: false,
+        is_to_contract: false,
+    };
+    let list: TokenTransactionList = serde_json::from_value(json!({
+        "code": "0",
+        "msg": "",
+        "data": {
+            "page": "1",
+            "limit": "20",
+            "total_page": "1",
+            "transaction_lists": []
+        }
+    }))
+    .unwrap();
+    assert!(list.transfers().is_empty());
+
+    let transfer = Transfer::from(&tx);
+    assert_eq!(transfer.kind, TransferKind::Token);
+    assert_eq!(transfer.asset, "USDT");
+    assert_eq!(transfer.token_contract_address, "0xUsdt");
+    assert_eq!(transfer.status, TransferStatus::Success);
+    assert_eq!(transfer.index, None);
+    assert_eq!(transfer.height, Some(18_000_002));
+
+    let pending = Transfer::from(&TokenTransaction {
+        height: String::new(),
+        transaction_time: "n/a".to_string(),
+        ..tx
+    });
+    assert_eq!(pending.height, None);
+    assert_eq!(pending.time, None);
+}
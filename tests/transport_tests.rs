@@ -0,0 +1,37 @@
+mod common;
+
+use common::CannedTransport;
+use oklink::secret::ApiKey;
+use oklink::transport::HttpMethod;
+use oklink::Oklink;
+
+#[tokio::test]
+async fn test_requests_go_through_transport() {
+    let transport = CannedTransport::new(
+        r#"{"code": "0", "msg": "", "data": [{"label": "Binance: Hot Wallet", "address": "0xYourAddress"}]}"#,
+    );
+    let requests = transport.requests.clone();
+    let oklink = Oklink::with_transport("test_api_key".to_string(), transport);
+
+    let labels = oklink.address_entity_labels("0xYourAddress").await.unwrap();
+
+    assert_eq!(labels.data[0].label, "Binance: Hot Wallet");
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, HttpMethod::Get);
+    assert_eq!(
+        requests[0].url,
+        "https://www.oklink.com/api/v5/explorer/address/entity-labels"
+    );
+    assert!(requests[0]
+        .query
+        .contains(&("address".to_string(), "0xYourAddress".to_string())));
+    assert_eq!(
+        requests[0].api_key.as_ref().map(ApiKey::expose),
+        Some("test_api_key")
+    );
+    assert!(requests[0]
+        .headers
+        .iter()
+        .all(|(_, value)| !value.contains("test_api_key")));
+}
@@ -0,0 +1,137 @@
+use serde_json::json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use oklink::keys::{KeyPool, KeySelection};
+use oklink::transport::{
+    HttpRequest, HttpResponse, HttpTransport, TransportError, TransportFuture,
+};
+use oklink::Oklink;
+
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+const WRAPPED_KAIA: &str = "0x19aac5f612f524b754ca7e7c41cbfa2e981a4432";
+
+type Query = Vec<(String, String)>;
+
+// Returns one daily price point at `point_time` and records every query.
+// The first `failures` requests get a rate limit error instead.
+#[derive(Clone)]
+struct PriceTransport {
+    point_time: u64,
+    failures: Arc<AtomicUsize>,
+    queries: Arc<Mutex<Vec<Query>>>,
+}
+
+impl PriceTransport {
+    fn new(point_time: u64) -> Self {
+        PriceTransport {
+            point_time,
+            failures: Arc::new(AtomicUsize::new(0)),
+            queries: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    fn with_failures(self, failures: usize) -> Self {
+        self.failures.store(failures, Ordering::SeqCst);
+        self
+    }
+
+    fn requests(&self) -> usize {
+        self.queries.lock().unwrap().len()
+    }
+}
+
+impl HttpTransport for PriceTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        assert!(request.url.ends_with("tokenprice/historical"));
+        self.queries.lock().unwrap().push(request.query.clone());
+        let failed = self
+            .failures
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok();
+        let body = if failed {
+            json!({"code": "50011", "msg": "Too Many Requests", "data": []})
+        } else {
+            json!({
+                "code": "0",
+                "msg": "",
+                "data": [{"time": self.point_time.to_string(), "price": "0.25"}]
+            })
+        };
+        Box::pin(async move {
+            Ok(HttpResponse {
+                status: 200,
+                body: body.to_string().into_bytes(),
+            })
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_price_at_asks_for_the_close_before_the_end_of_the_day() {
+    let day = 19_000;
+    let transport = PriceTransport::new(day * DAY_MS);
+    let oklink = Oklink::with_transport("key", transport.clone());
+
+    let price = oklink.price_at("", day * DAY_MS + 1234).await.unwrap();
+    // Cached: closed days are not fetched again.
+    oklink.price_at("", day * DAY_MS + 5678).await.unwrap();
+
+    assert_eq!(price, Some(0.25));
+    assert_eq!(transport.requests(), 1);
+    let query = transport.queries.lock().unwrap()[0].clone();
+    let expected: Query = [
+        ("chainId", "8217"),
+        ("tokenContractAddress", WRAPPED_KAIA),
+        ("period", "1d"),
+        ("after", &((day + 1) * DAY_MS).to_string()),
+        ("limit", "1"),
+    ]
+    .iter()
+    .map(|(k, v)| (k.to_string(), v.to_string()))
+    .collect();
+    assert_eq!(query, expected);
+}
+
+#[tokio::test]
+async fn test_price_at_ignores_points_from_another_day() {
+    let day = 19_000;
+    let oklink = Oklink::with_transport("key", PriceTransport::new((day - 3) * DAY_MS));
+
+    assert_eq!(oklink.price_at("0xUsdt", day * DAY_MS).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_price_at_does_not_cache_the_open_day() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+    let transport = PriceTransport::new(now / DAY_MS * DAY_MS);
+    let oklink = Oklink::with_transport("key", transport.clone());
+
+    assert_eq!(oklink.price_at("0xUsdt", now).await.unwrap(), Some(0.25));
+    oklink.price_at("0xUsdt", now).await.unwrap();
+
+    assert_eq!(transport.requests(), 2);
+}
+
+#[tokio::test]
+async fn test_price_at_does_not_cache_error_responses() {
+    let day = 19_000;
+    let transport = PriceTransport::new(day * DAY_MS).with_failures(1);
+    // Skips the short cooldown the rate limit error puts the only key on.
+    let pool = KeyPool::new(KeySelection::RoundRobin)
+        .with_cooldown(Duration::ZERO)
+        .key("default", "key", None);
+    let oklink = Oklink::with_transport(pool, transport.clone());
+
+    let error = oklink.price_at("0xUsdt", day * DAY_MS).await.unwrap_err();
+    assert!(matches!(error, TransportError::Api { ref code, .. } if code == "50011"));
+    assert_eq!(
+        oklink.price_at("0xUsdt", day * DAY_MS).await.unwrap(),
+        Some(0.25)
+    );
+    assert_eq!(transport.requests(), 2);
+}
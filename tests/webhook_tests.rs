@@ -0,0 +1,185 @@
+use mockito::{mock, Matcher};
+use oklink::webhook::{
+    number_occurrences, sign, WebhookDispatcher, WebhookEndpoint, WebhookEvent, WebhookEventKind,
+};
+use std::time::Duration;
+
+fn test_event() -> WebhookEvent {
+    WebhookEvent {
+        idempotency_key: "test_key".to_string(),
+        kind: WebhookEventKind::AddressTokenTransfer,
+        watched: "0xYourAddress".to_string(),
+        tx_id: "0xTxId".to_string(),
+        height: "100".to_string(),
+        transaction_time: "1700000000000".to_string(),
+        from: "0xYourAddress".to_string(),
+        to: "0xOtherAddress".to_string(),
+        token_contract_address: "0xToken".to_string(),
+        token_id: "".to_string(),
+        amount: "10".to_string(),
+        symbol: "USDT".to_string(),
+        occurrence: 0,
+    }
+}
+
+#[tokio::test]
+async fn test_webhook_dispatch_signs_and_sends_idempotency_key() {
+    let event = test_event();
+    let body = serde_json::to_vec(&event).unwrap();
+    let _m = mock("POST", "/hooks/signed")
+        .match_header("idempotency-key", "test_key")
+        .match_header("x-oklink-signature", Matcher::Exact(sign("secret", &body)))
+        .with_status(200)
+        .expect(1)
+        .create();
+
+    let endpoint = WebhookEndpoint {
+        url: format!("{}/hooks/signed", mockito::server_url()),
+        secret: "secret".to_string(),
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let mut dispatcher = WebhookDispatcher::new(vec![endpoint], dir.path().to_path_buf());
+    let dispatched = dispatcher
+        .dispatch_all(vec![event.clone(), event])
+        .await
+        .unwrap();
+
+    assert_eq!(dispatched.len(), 1);
+    _m.assert();
+}
+
+#[tokio::test]
+async fn test_webhook_dispatch_writes_dead_letter_after_retries() {
+    let _m = mock("POST", "/hooks/failing")
+        .with_status(500)
+        .expect(3)
+        .create();
+
+    let endpoint = WebhookEndpoint {
+        url: format!("{}/hooks/failing", mockito::server_url()),
+        secret: "secret".to_string(),
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let mut dispatcher = WebhookDispatcher::new(vec![endpoint], dir.path().join("dead_letters"))
+        .with_retry(3, Duration::from_millis(1));
+    dispatcher.dispatch(&test_event()).await.unwrap();
+
+    _m.assert();
+    let letters = dispatcher.dead_letters().unwrap();
+    assert_eq!(letters.len(), 1);
+    assert_eq!(letters[0].attempts, 3);
+    assert_eq!(letters[0].event.idempotency_key, "test_key");
+}
+
+#[test]
+fn test_identical_transfers_in_one_tx_get_distinct_keys() {
+    let events = number_occurrences(vec![test_event(), test_event()]);
+
+    assert_eq!(events[0].idempotency_key, "test_key");
+    assert_eq!(events[1].occurrence, 1);
+    assert_ne!(events[1].idempotency_key, events[0].idempotency_key);
+}
+
+#[tokio::test]
+async fn test_delivered_log_survives_restart() {
+    let _m = mock("POST", "/hooks/persistent")
+        .with_status(200)
+        .expect(1)
+        .create();
+
+    let endpoint = WebhookEndpoint {
+        url: format!("{}/hooks/persistent", mockito::server_url()),
+        secret: "secret".to_string(),
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let log = dir.path().join("delivered.log");
+
+    let mut dispatcher = WebhookDispatcher::new(vec![endpoint.clone()], dir.path().to_path_buf())
+        .with_delivered_log(log.clone())
+        .unwrap();
+    assert_eq!(
+        dispatcher
+            .dispatch_all(vec![test_event()])
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    let mut restarted = WebhookDispatcher::new(vec![endpoint], dir.path().to_path_buf())
+        .with_delivered_log(log)
+        .unwrap();
+    assert!(restarted
+        .dispatch_all(vec![test_event()])
+        .await
+        .unwrap()
+        .is_empty());
+    _m.assert();
+}
+
+fn event_with_key(key: &str) -> WebhookEvent {
+    WebhookEvent {
+        idempotency_key: key.to_string(),
+        ..test_event()
+    }
+}
+
+#[tokio::test]
+async fn test_delivered_events_are_bounded_by_capacity() {
+    let _m = mock("POST", "/hooks/bounded")
+        .with_status(200)
+        .expect(7)
+        .create();
+
+    let endpoint = WebhookEndpoint {
+        url: format!("{}/hooks/bounded", mockito::server_url()),
+        secret: "secret".to_string(),
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let log = dir.path().join("delivered.log");
+    let mut dispatcher = WebhookDispatcher::new(vec![endpoint.clone()], dir.path().to_path_buf())
+        .with_delivered_capacity(2)
+        .with_delivered_log(log.clone())
+        .unwrap();
+    let events: Vec<_> = ["a", "b", "c", "d", "e", "f"]
+        .into_iter()
+        .map(event_with_key)
+        .collect();
+    assert_eq!(dispatcher.dispatch_all(events).await.unwrap().len(), 6);
+
+    // The log was compacted to the remembered keys along the way.
+    let lines = std::fs::read_to_string(&log).unwrap().lines().count();
+    assert!(lines <= 4, "log kept {} lines", lines);
+
+    // "a" was forgotten and goes out again; "f" is still remembered.
+    let mut restarted = WebhookDispatcher::new(vec![endpoint], dir.path().to_path_buf())
+        .with_delivered_capacity(2)
+        .with_delivered_log(log)
+        .unwrap();
+    let dispatched = restarted
+        .dispatch_all(vec![event_with_key("a"), event_with_key("f")])
+        .await
+        .unwrap();
+    assert_eq!(dispatched.len(), 1);
+    assert_eq!(dispatched[0].idempotency_key, "a");
+    _m.assert();
+}
+
+#[tokio::test]
+async fn test_webhook_delivery_times_out() {
+    // Accepts connections but never answers.
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = WebhookEndpoint {
+        url: format!("http://{}/hooks/stalled", listener.local_addr().unwrap()),
+        secret: "secret".to_string(),
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let mut dispatcher = WebhookDispatcher::new(vec![endpoint], dir.path().to_path_buf())
+        .with_timeout(Duration::from_millis(50))
+        .with_retry(1, Duration::ZERO);
+    dispatcher.dispatch(&test_event()).await.unwrap();
+
+    let letters = dispatcher.dead_letters().unwrap();
+    assert_eq!(letters.len(), 1);
+    drop(listener);
+}